@@ -9,7 +9,6 @@ extern crate quickersort;
 
 use std::io::{self, Read};
 use std::cmp::Ordering::{self, Less, Equal, Greater};
-use std::cell::Cell;
 
 fn main() {
     let mut input = io::stdin().bytes().map(|x|x.unwrap_or(b'\0'));
@@ -22,7 +21,7 @@ fn main() {
     }
     // The rest of the input is directions on what the comparator should return.
     let directions: Vec<Ordering> = input.map(|x| match x { 0 => Less, 1 => Equal, _ => Greater }).take(1024*1024).collect(); // also cap this at 1MiB
-    let pos = Cell::new(0);
+    let mut pos = 0;
     let count = directions.len();
-    quickersort::sort_by(&mut list, &move |_, _| { pos.set((pos.get() + 1)); if pos.get() >= count { Equal } else { directions[pos.get()] } });
+    quickersort::sort_by_mut(&mut list, &mut |_, _| { pos += 1; if pos >= count { Equal } else { directions[pos] } });
 }
@@ -2,6 +2,7 @@
 // (C) 2015 Michael Howell <michael@notriddle.com>
 // This file is licensed under the same terms as Rust itself.
 
+#![cfg(feature = "unstable")]
 #![feature(test)]
 
 extern crate quickersort;
@@ -99,7 +100,7 @@ fn sort_huge(b: &mut Bencher) {
 #[bench]
 fn sort_enormous(b: &mut Bencher) {
     let mut rng = weak_rng();
-    let n = 1_00_000;
+    let n = 100_000;
     let mut v = rng.gen_iter::<i64>().take(n).collect::<Vec<i64>>();
     b.iter(|| {
         rng.shuffle(&mut v[..]);
@@ -206,3 +207,29 @@ fn sort_strings(b: &mut Bencher) {
     });
     b.bytes = bytes as u64;
 }
+
+// One in ten is a `None`.
+fn options_large() -> Vec<Option<i64>> {
+    let mut rng = weak_rng();
+    (0 .. 10_000).map(|_| if rng.gen_weighted_bool(10) { None } else { Some(rng.gen()) }).collect()
+}
+
+#[bench]
+fn sort_options_large(b: &mut Bencher) {
+    let v = options_large();
+    b.iter(|| {
+        let mut w = v.clone();
+        quickersort::sort_options(&mut w[..], quickersort::NullsPlacement::First);
+    });
+    b.bytes = (v.len() * mem::size_of::<Option<i64>>()) as u64;
+}
+
+#[bench]
+fn sort_options_large_sort(b: &mut Bencher) {
+    let v = options_large();
+    b.iter(|| {
+        let mut w = v.clone();
+        sort(&mut w[..]);
+    });
+    b.bytes = (v.len() * mem::size_of::<Option<i64>>()) as u64;
+}
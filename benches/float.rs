@@ -2,13 +2,13 @@
 // (C) 2015 Michael Howell <michael@notriddle.com>
 // This file is licensed under the same terms as Rust itself.
 
+#![cfg(feature = "unstable")]
 #![feature(test)]
 
 extern crate quickersort;
 extern crate test;
 extern crate rand;
 
-#[cfg(feature = "float")]
 mod bench {
 
 use std::f64;
@@ -26,6 +26,17 @@ fn bench_float_sort_float_f64_large(bench: &mut ::test::Bencher) {
     bench.bytes = (::std::mem::size_of::<f64>() * v.len()) as u64;
 }
 
+#[bench]
+#[cfg(feature = "alloc")]
+fn bench_float_radix_sort_floats_f64_large(bench: &mut ::test::Bencher) {
+    let v = f64_large();
+    bench.iter(|| {
+        let mut w = v.clone();
+        quickersort::radix_sort_floats(&mut w[..]);
+    });
+    bench.bytes = (::std::mem::size_of::<f64>() * v.len()) as u64;
+}
+
 #[bench]
 fn bench_float_sort_by_f64_large(bench: &mut ::test::Bencher) {
     let v = f64_large();
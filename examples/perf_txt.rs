@@ -15,6 +15,7 @@ use num_traits::PrimInt;
 enum Algorithm {
     Std,
     Quickersort,
+    Radix,
 }
 
 #[derive(Copy,Clone)]
@@ -95,16 +96,17 @@ fn run_test(algorithm: Algorithm, pattern: Pattern, variant: Variant, size: usiz
         match algorithm {
             Algorithm::Std => v.sort(),
             Algorithm::Quickersort => quickersort::sort(&mut v),
+            Algorithm::Radix => quickersort::radix_sort(&mut v),
         }
         let elapsed = start.elapsed();
-        time += elapsed.as_secs() * 1000_000_000 + elapsed.subsec_nanos() as u64;
+        time += elapsed.as_secs() * 1_000_000_000 + elapsed.subsec_nanos() as u64;
     }
     let duration = time / TRIAL_COUNT;
     size as f64 / (duration as f64 / 1000f64)
 }
 
 fn main() {
-    println!("{: >15}{: >15}{: >15}{: >15}{: >15}{: >15}{: >15}", "size", "m", "pattern", "variant", "quicker", "std", "quicker/std");
+    println!("{: >15}{: >15}{: >15}{: >15}{: >15}{: >15}{: >15}{: >15}{: >15}", "size", "m", "pattern", "variant", "quicker", "std", "radix", "quicker/std", "radix/quicker");
     for size_pow in 0 .. 6 {
         let size = 10.pow(1+size_pow);
         for m_pow in 0 .. log2(size) {
@@ -113,7 +115,8 @@ fn main() {
                 for &variant in &[Variant::Ident, Variant::Reverse, Variant::ReverseFront, Variant::ReverseBack, Variant::Sorted, Variant::Dither] {
                     let throughput_std = run_test(Algorithm::Std, pattern, variant, size, m);
                     let throughput_qs = run_test(Algorithm::Quickersort, pattern, variant, size, m);
-                    println!("{: >15}{: >15}{: >15}{: >15}{: >15.1}{: >15.1}{: >15.1}", size, m, pattern, variant, throughput_qs, throughput_std, throughput_qs / throughput_std);
+                    let throughput_radix = run_test(Algorithm::Radix, pattern, variant, size, m);
+                    println!("{: >15}{: >15}{: >15}{: >15}{: >15.1}{: >15.1}{: >15.1}{: >15.1}{: >15.1}", size, m, pattern, variant, throughput_qs, throughput_std, throughput_radix, throughput_qs / throughput_std, throughput_radix / throughput_qs);
                 }
             }
         }
@@ -0,0 +1,172 @@
+// This file is licensed under the same terms as Rust itself.
+
+use core::cmp::Ordering;
+#[cfg(feature = "alloc")]
+use core::cmp::Ordering::*;
+use core::fmt;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use super::sort::{try_sort_by, apply_permutation};
+
+/// The rule of a total order that `checked_sort_by` found broken.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ComparatorErrorKind {
+    /// Comparing two elements both ways did not give opposite results, or
+    /// comparing an element with itself did not give `Equal`.
+    Antisymmetry,
+    /// The first element is not greater than the second, and the second is
+    /// not greater than the third, but the first is greater than the third.
+    Transitivity,
+    /// The sort put the first element before the second, but it is greater,
+    /// and no single element is between them. The order is inconsistent
+    /// through a longer chain of comparisons.
+    Unsorted,
+}
+
+/// The error returned by `checked_sort_by` when the comparison function is
+/// not a total order.
+///
+/// The elements are given by their positions in the slice before sorting,
+/// which is left as it was.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ComparatorError {
+    kind: ComparatorErrorKind,
+    comparisons: [(usize, usize, Ordering); 3],
+    len: usize,
+}
+
+impl ComparatorError {
+    /// Returns the rule that was broken.
+    pub fn kind(&self) -> ComparatorErrorKind {
+        self.kind
+    }
+
+    /// Returns the comparisons that break it, as the positions of the two
+    /// elements compared and the result.
+    ///
+    /// There are two for `Antisymmetry`, a pair compared both ways. There
+    /// are three for `Transitivity`: the first element with the second, the
+    /// second with the third, and the first with the third. There is one for
+    /// `Unsorted`.
+    pub fn comparisons(&self) -> &[(usize, usize, Ordering)] {
+        &self.comparisons[..self.len]
+    }
+}
+
+impl fmt::Display for ComparatorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self.kind {
+            ComparatorErrorKind::Antisymmetry => "comparison function is not antisymmetric",
+            ComparatorErrorKind::Transitivity => "comparison function is not transitive",
+            ComparatorErrorKind::Unsorted => "comparison function is not a total order",
+        })?;
+        for (n, &(i, j, ordering)) in self.comparisons().iter().enumerate() {
+            let sep = if n == 0 { ": " } else { ", " };
+            write!(f, "{}v[{}] vs v[{}] is {:?}", sep, i, j, ordering)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for ComparatorError {}
+
+/// Sort using a comparison function, and check that it is a total order.
+///
+/// Every comparison made while sorting, whether partitioning or insertion
+/// sorting, is also made the other way around, and the results must be
+/// opposite. This is the only check made while sorting. Transitivity is
+/// checked by a final pass over the sorted elements, which checks that each
+/// element is not greater than the next one, and that the first one is not
+/// greater than any of them. If one is, this looks for an element between
+/// the two that shows that the comparison function is not transitive.
+///
+/// On success, `v` is sorted as by `sort_by`. On error, `v` is left as it
+/// was, and the error tells the positions of the elements involved and what
+/// `compare` returned for them. Unlike the "assert_working_compare" feature,
+/// this works in release builds and does not panic, but it allocates a
+/// permutation of `v` and calls `compare` about twice as often as `sort_by`.
+/// An inconsistent comparison function is not always caught, because not
+/// every pair of elements is compared.
+///
+/// # Example
+///
+///     use quickersort::ComparatorErrorKind;
+///     use std::cmp::Ordering::*;
+///
+///     # #[cfg(feature = "alloc")] {
+///     let mut v = [3, 1, 2];
+///     assert_eq!(quickersort::checked_sort_by(&mut v, &|a, b| a.cmp(b)), Ok(()));
+///     assert_eq!(v, [1, 2, 3]);
+///
+///     // Everything is less than everything else.
+///     let err = quickersort::checked_sort_by(&mut v, &|_, _| Less).unwrap_err();
+///     assert_eq!(err.kind(), ComparatorErrorKind::Antisymmetry);
+///     assert_eq!(err.comparisons()[0].2, err.comparisons()[1].2);
+///     assert_eq!(v, [1, 2, 3]);
+///     # }
+#[cfg(feature = "alloc")]
+pub fn checked_sort_by<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C) -> Result<(), ComparatorError> {
+    let both_ways = |i: usize, j: usize| {
+        let (ij, ji) = (compare(&v[i], &v[j]), compare(&v[j], &v[i]));
+        if ij == ji.reverse() {
+            Ok(ij)
+        } else {
+            Err(error(ComparatorErrorKind::Antisymmetry, &[(i, j, ij), (j, i, ji)]))
+        }
+    };
+
+    let mut perm: Vec<usize> = (0..v.len()).collect();
+    try_sort_by(&mut perm, &mut |&i, &j| both_ways(i, j))?;
+
+    // Each element must not be less than the one before it, nor than the
+    // first one, which also catches cycles through elements far apart.
+    let mut first_prev = Equal;
+    for k in 1..perm.len() {
+        let (first, i, j) = (perm[0], perm[k - 1], perm[k]);
+        let ij = both_ways(i, j)?;
+        if ij == Greater {
+            for m in 0..v.len() {
+                let (im, mj) = (compare(&v[i], &v[m]), compare(&v[m], &v[j]));
+                if im != Greater && mj != Greater {
+                    return Err(error(ComparatorErrorKind::Transitivity, &[(i, m, im), (m, j, mj), (i, j, Greater)]));
+                }
+            }
+            return Err(error(ComparatorErrorKind::Unsorted, &[(i, j, Greater)]));
+        }
+        let first_j = both_ways(first, j)?;
+        if first_j == Greater {
+            return Err(error(ComparatorErrorKind::Transitivity, &[(first, i, first_prev), (i, j, ij), (first, j, Greater)]));
+        }
+        first_prev = first_j;
+    }
+
+    apply_permutation(v, &mut perm, |p| p);
+    Ok(())
+}
+
+/// Sort using a conversion function, and check that the order of the keys
+/// is a total order.
+///
+/// See `checked_sort_by`.
+#[cfg(feature = "alloc")]
+pub fn checked_sort_by_key<T, K: Ord, F: Fn(&T) -> K>(v: &mut [T], key: F) -> Result<(), ComparatorError> {
+    checked_sort_by(v, &|a, b| key(a).cmp(&key(b)))
+}
+
+/// Sort using the default comparison function, and check that the `Ord`
+/// implementation is a total order.
+///
+/// See `checked_sort_by`.
+#[cfg(feature = "alloc")]
+pub fn checked_sort<T: Ord>(v: &mut [T]) -> Result<(), ComparatorError> {
+    checked_sort_by(v, &|a, b| a.cmp(b))
+}
+
+#[cfg(feature = "alloc")]
+fn error(kind: ComparatorErrorKind, found: &[(usize, usize, Ordering)]) -> ComparatorError {
+    let mut comparisons = [(0, 0, Equal); 3];
+    comparisons[..found.len()].copy_from_slice(found);
+    ComparatorError { kind, comparisons, len: found.len() }
+}
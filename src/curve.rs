@@ -0,0 +1,288 @@
+// This file is licensed under the same terms as Rust itself.
+
+use core::marker::PhantomData;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use super::sort::{sort_by, apply_permutation};
+#[cfg(feature = "alloc")]
+use super::radix::{RadixKey, lsd_radix_sort, RADIX_SORT_THRESHOLD};
+
+/// Points that can be sorted along a space filling curve.
+///
+/// This is implemented for `[f32; 2]`, `[f64; 2]` and `[f64; 3]`. Each
+/// coordinate is quantized to 32 bits in two dimensions, and to 21 bits in
+/// three, so that the key of a point fits in a `u64`.
+pub trait CurvePoint: Copy {
+    /// The number of coordinates, which must be 2 or 3.
+    const DIM: usize;
+
+    /// Returns coordinate `i`, where `i` is less than `DIM`.
+    fn coord(&self, i: usize) -> f64;
+}
+
+impl CurvePoint for [f32; 2] {
+    const DIM: usize = 2;
+
+    #[inline]
+    fn coord(&self, i: usize) -> f64 {
+        self[i] as f64
+    }
+}
+
+impl CurvePoint for [f64; 2] {
+    const DIM: usize = 2;
+
+    #[inline]
+    fn coord(&self, i: usize) -> f64 {
+        self[i]
+    }
+}
+
+impl CurvePoint for [f64; 3] {
+    const DIM: usize = 3;
+
+    #[inline]
+    fn coord(&self, i: usize) -> f64 {
+        self[i]
+    }
+}
+
+/// The box that the coordinates of points are quantized in.
+///
+/// Each side of the box is split into equal cells, and every coordinate is
+/// replaced by the number of its cell. Coordinates outside the box, including
+/// infinities, are moved to the nearest side. `-0` and `+0` are in the same
+/// cell. Using the same box for several slices quantizes them the same way.
+///
+/// # Example
+///
+///     use quickersort::BoundingBox;
+///
+///     let tile = BoundingBox::new([0.0f64, 0.0], [256.0, 256.0]);
+///     let mut points = [[200.0, 10.0], [3.0, 4.0], [300.0, -1.0]];
+///     # #[cfg(feature = "alloc")] {
+///     quickersort::sort_points_morton_with(&mut points, &tile);
+///     assert_eq!(points, [[3.0, 4.0], [200.0, 10.0], [300.0, -1.0]]);
+///     # }
+#[derive(Debug)]
+pub struct BoundingBox<P> {
+    min: [f64; 3],
+    max: [f64; 3],
+    point: PhantomData<P>,
+}
+
+impl<P> Clone for BoundingBox<P> {
+    fn clone(&self) -> BoundingBox<P> {
+        *self
+    }
+}
+
+impl<P> Copy for BoundingBox<P> {}
+
+impl<P: CurvePoint> BoundingBox<P> {
+    /// Creates a box with the corners `min` and `max`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is not finite, or is greater in `min` than in
+    /// `max`.
+    pub fn new(min: P, max: P) -> BoundingBox<P> {
+        let mut b = BoundingBox { min: [0.0; 3], max: [0.0; 3], point: PhantomData };
+        for i in 0..P::DIM {
+            let (lo, hi) = (min.coord(i), max.coord(i));
+            assert!(lo.is_finite() && hi.is_finite(), "bounding box coordinates must be finite");
+            assert!(lo <= hi, "bounding box minimum {} is greater than maximum {}", lo, hi);
+            b.min[i] = lo;
+            b.max[i] = hi;
+        }
+        b
+    }
+
+    /// Returns the smallest box that contains the finite coordinates of
+    /// `points`.
+    ///
+    /// NaNs and infinities are left out. If there are no finite coordinates
+    /// on an axis, the box is `0` wide there.
+    pub fn of(points: &[P]) -> BoundingBox<P> {
+        let mut b = BoundingBox { min: [0.0; 3], max: [0.0; 3], point: PhantomData };
+        for i in 0..P::DIM {
+            let mut finite = points.iter().map(|p| p.coord(i)).filter(|x| x.is_finite());
+            if let Some(first) = finite.next() {
+                let (lo, hi) = finite.fold((first, first), |(lo, hi), x| {
+                    (if x < lo { x } else { lo }, if x > hi { x } else { hi })
+                });
+                b.min[i] = lo;
+                b.max[i] = hi;
+            }
+        }
+        b
+    }
+
+    /// Returns the cell of every coordinate of `p`, or `None` if one of them
+    /// is NaN.
+    #[cfg(feature = "alloc")]
+    fn cells(&self, p: &P, bits: u32) -> Option<[u64; 3]> {
+        let last = (1u64 << bits) - 1;
+        let mut cells = [0; 3];
+        for (i, cell) in cells[..P::DIM].iter_mut().enumerate() {
+            let x = p.coord(i);
+            if x.is_nan() {
+                return None;
+            }
+            // Outside the box, or on an axis where it is `0` wide, this is
+            // infinite or NaN, and rounds to a side.
+            let t = (x - self.min[i]) / (self.max[i] - self.min[i]);
+            *cell = if t >= 1.0 {
+                last
+            } else if t > 0.0 {
+                // Rounding can still give the next cell after the last.
+                ((t * (last + 1) as f64) as u64).min(last)
+            } else {
+                0
+            };
+        }
+        Some(cells)
+    }
+}
+
+/// Sorts points along a Z-order curve, in the box around their finite
+/// coordinates.
+///
+/// See `sort_points_morton_with`.
+///
+/// # Example
+///
+///     let mut points = [[1.0f32, 1.0], [0.0, 0.0], [1.0, 0.0], [0.0, 1.0]];
+///     quickersort::sort_points_morton(&mut points);
+///     assert_eq!(points, [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0]]);
+#[cfg(feature = "alloc")]
+pub fn sort_points_morton<P: CurvePoint>(v: &mut [P]) {
+    let bounds = BoundingBox::of(v);
+    sort_points_morton_with(v, &bounds);
+}
+
+/// Sorts points along a Z-order curve, also known as Morton order, after
+/// quantizing them in `bounds`.
+///
+/// The key of a point interleaves the bits of the cells of its coordinates,
+/// with the first coordinate lowest, and the keys are radix sorted as in
+/// `radix_sort_by_key`. Points in the same cell keep their relative order.
+/// Points with a NaN coordinate are moved to the end, in the order they
+/// were in.
+#[cfg(feature = "alloc")]
+pub fn sort_points_morton_with<P: CurvePoint>(v: &mut [P], bounds: &BoundingBox<P>) {
+    sort_by_curve_key(v, bounds, |cells, bits| {
+        cells[..P::DIM].reverse();
+        interleave(cells, P::DIM, bits)
+    });
+}
+
+/// Sorts points along a Hilbert curve, in the box around their finite
+/// coordinates.
+///
+/// See `sort_points_hilbert_with`.
+///
+/// # Example
+///
+///     let mut points = [[1.0, 1.0], [0.0, 0.0], [1.0, 0.0], [0.0, 1.0]];
+///     quickersort::sort_points_hilbert(&mut points);
+///     assert_eq!(points, [[0.0, 0.0], [0.0, 1.0], [1.0, 1.0], [1.0, 0.0]]);
+#[cfg(feature = "alloc")]
+pub fn sort_points_hilbert<P: CurvePoint>(v: &mut [P]) {
+    let bounds = BoundingBox::of(v);
+    sort_points_hilbert_with(v, &bounds);
+}
+
+/// Sorts points along a Hilbert curve after quantizing them in `bounds`.
+///
+/// Unlike on a Z-order curve, points next to each other on a Hilbert curve
+/// are always in neighbouring cells, which keeps nearby points closer
+/// together in the slice. Computing the keys takes a little longer.
+/// Otherwise, this works like `sort_points_morton_with`.
+#[cfg(feature = "alloc")]
+pub fn sort_points_hilbert_with<P: CurvePoint>(v: &mut [P], bounds: &BoundingBox<P>) {
+    sort_by_curve_key(v, bounds, |cells, bits| {
+        hilbert_transpose(&mut cells[..P::DIM], bits);
+        interleave(cells, P::DIM, bits)
+    });
+}
+
+/// Sort `v` stably by the curve `key` of the cells of each point, with the
+/// points with NaNs last.
+#[cfg(feature = "alloc")]
+fn sort_by_curve_key<P, F>(v: &mut [P], bounds: &BoundingBox<P>, key: F)
+    where P: CurvePoint, F: Fn(&mut [u64; 3], u32) -> u64
+{
+    assert!(P::DIM == 2 || P::DIM == 3, "points must have 2 or 3 coordinates");
+    let bits = 64 / P::DIM as u32;
+    let mut keys: Vec<((bool, u64), usize)> = v.iter().enumerate().map(|(i, p)| {
+        match bounds.cells(p, bits) {
+            Some(mut cells) => ((false, key(&mut cells, bits)), i),
+            None => ((true, 0), i),
+        }
+    }).collect();
+
+    let bytes = <(bool, u64)>::BYTES;
+    if keys.len() < RADIX_SORT_THRESHOLD * bytes {
+        sort_by(&mut keys, &|a, b| a.cmp(b));
+    } else {
+        lsd_radix_sort(&mut keys, &|k: &((bool, u64), usize)| k.0);
+    }
+    apply_permutation(v, &mut keys, |k| &mut k.1);
+}
+
+/// Interleave the low `bits` bits of the first `dim` cells, with the first
+/// cell in the highest bit of each group.
+#[cfg(feature = "alloc")]
+fn interleave(cells: &[u64; 3], dim: usize, bits: u32) -> u64 {
+    let mut key = 0;
+    for bit in (0..bits).rev() {
+        for cell in cells[..dim].iter() {
+            key = key << 1 | (cell >> bit & 1);
+        }
+    }
+    key
+}
+
+/// Turn the cells of a point into the transposed form of its index on a
+/// Hilbert curve of `bits` levels, which `interleave` turns into the index.
+///
+/// This is the algorithm from John Skilling, "Programming the Hilbert
+/// curve", AIP Conference Proceedings 707, 2004.
+#[cfg(feature = "alloc")]
+fn hilbert_transpose(x: &mut [u64], bits: u32) {
+    let n = x.len();
+    let top = 1 << (bits - 1);
+    let mut q = top;
+    while q > 1 {
+        let p = q - 1;
+        for i in 0..n {
+            if x[i] & q != 0 {
+                // Invert the low bits of the first cell.
+                x[0] ^= p;
+            } else {
+                // Exchange the low bits of the first cell and this one.
+                let t = (x[0] ^ x[i]) & p;
+                x[0] ^= t;
+                x[i] ^= t;
+            }
+        }
+        q >>= 1;
+    }
+    // Gray encode.
+    for i in 1..n {
+        x[i] ^= x[i - 1];
+    }
+    let mut t = 0;
+    let mut q = top;
+    while q > 1 {
+        if x[n - 1] & q != 0 {
+            t ^= q - 1;
+        }
+        q >>= 1;
+    }
+    for c in x.iter_mut() {
+        *c ^= t;
+    }
+}
@@ -0,0 +1,156 @@
+// This file is licensed under the same terms as Rust itself.
+
+use core::ops::Range;
+use super::float::Float;
+use super::radix::RadixKey;
+
+/// How `cluster_floats` and `dedup_floats_with_tolerance` measure the
+/// distance between two numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ToleranceMode {
+    /// The difference between the numbers.
+    Absolute,
+    /// The number of steps from one representable number of the type to
+    /// the next that it takes to get from one number to the other, or units
+    /// in the last place. `-0` and `+0` count as a single number, so the
+    /// smallest positive and negative numbers are two units apart.
+    Ulps,
+}
+
+/// Splits floating point numbers sorted by `sort_floats` into clusters of
+/// numbers within `eps` of each other.
+///
+/// Returns an iterator over the positions of each cluster. A cluster starts
+/// at the smallest number that is not yet in a cluster, and contains the
+/// numbers after it that are at most `eps` away from that number, measured
+/// as `mode` says. The clusters are therefore never wider than `eps`.
+///
+/// Equal numbers are always in the same cluster, so `-0` and `+0` are, even
+/// if `eps` is zero. All NaNs form a single cluster at the end, and a NaN is
+/// never in the same cluster as a number.
+///
+/// If `v` is not sorted, the clusters are still ranges that cover `v`, but
+/// are otherwise unspecified.
+///
+/// # Panics
+///
+/// Panics if `eps` is negative or NaN, or if `mode` is `Ulps` and the
+/// `order_key` of `T` is wider than 128 bits.
+///
+/// # Example
+///
+///     use quickersort::{cluster_floats, ToleranceMode};
+///
+///     let v = [0.9999999, 1.0000001, 2.0, 2.05, 2.2, ::std::f64::NAN];
+///     let clusters: Vec<_> = cluster_floats(&v, 0.1, ToleranceMode::Absolute).collect();
+///     assert_eq!(clusters, [0..2, 2..4, 4..5, 5..6]);
+pub fn cluster_floats<'a, T: Float + Into<f64>>(v: &'a [T], eps: f64, mode: ToleranceMode) -> Clusters<'a, T> {
+    assert!(eps >= 0.0, "tolerance {} is negative or NaN", eps);
+    Clusters { v, start: 0, eps, mode }
+}
+
+/// Removes all but the first number of each cluster of floating point
+/// numbers sorted by `sort_floats`, as found by `cluster_floats`.
+///
+/// The remaining numbers are moved to the front of `v`, in order, and their
+/// count is returned. The rest of `v` is left in an unspecified order. So
+/// every bucket of numbers within `eps` is replaced by its smallest number,
+/// `-0` and `+0` become `-0`, and all NaNs become a single NaN.
+///
+/// # Panics
+///
+/// Panics if `eps` is negative or NaN, or if `mode` is `Ulps` and the
+/// `order_key` of `T` is wider than 128 bits.
+///
+/// # Example
+///
+///     use quickersort::{dedup_floats_with_tolerance, sort_floats, ToleranceMode};
+///
+///     let mut v = vec![1.0000001f32, 3.0, 0.9999999, 1.0, 3.0000002];
+///     sort_floats(&mut v);
+///     let len = dedup_floats_with_tolerance(&mut v, 4.0, ToleranceMode::Ulps);
+///     v.truncate(len);
+///     assert_eq!(v, [0.9999999, 3.0]);
+pub fn dedup_floats_with_tolerance<T: Float + Into<f64>>(v: &mut [T], eps: f64, mode: ToleranceMode) -> usize {
+    assert!(eps >= 0.0, "tolerance {} is negative or NaN", eps);
+    let mut len = 0;
+    let mut start = 0;
+    while start < v.len() {
+        let end = cluster_end(v, start, eps, mode);
+        v.swap(len, start);
+        len += 1;
+        start = end;
+    }
+    len
+}
+
+/// An iterator over the clusters of a sorted slice of floating point
+/// numbers.
+///
+/// This `struct` is created by `cluster_floats`.
+#[derive(Clone, Debug)]
+pub struct Clusters<'a, T: 'a> {
+    v: &'a [T],
+    start: usize,
+    eps: f64,
+    mode: ToleranceMode,
+}
+
+impl<'a, T: Float + Into<f64>> Iterator for Clusters<'a, T> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Range<usize>> {
+        if self.start == self.v.len() {
+            return None;
+        }
+        let start = self.start;
+        self.start = cluster_end(self.v, start, self.eps, self.mode);
+        Some(start..self.start)
+    }
+}
+
+/// Returns the end of the cluster that starts at `start`.
+fn cluster_end<T: Float + Into<f64>>(v: &[T], start: usize, eps: f64, mode: ToleranceMode) -> usize {
+    let first = v[start];
+    let mut end = start + 1;
+    if first.is_nan() {
+        while end < v.len() && v[end].is_nan() {
+            end += 1;
+        }
+        return end;
+    }
+    while end < v.len() && within(first, v[end], eps, mode) {
+        end += 1;
+    }
+    end
+}
+
+/// Returns whether `b` is within `eps` of `a`, where `a` is not NaN.
+fn within<T: Float + Into<f64>>(a: T, b: T, eps: f64, mode: ToleranceMode) -> bool {
+    if b.is_nan() {
+        return false;
+    }
+    // Also true for `-0` and `+0`, and for equal infinities.
+    if a == b {
+        return true;
+    }
+    let (a, b) = if a < b { (a, b) } else { (b, a) };
+    match mode {
+        ToleranceMode::Absolute => b.into() - a.into() <= eps,
+        ToleranceMode::Ulps => {
+            let mut distance = key(b) - key(a);
+            // `-0` and `+0` have consecutive keys, but are the same number.
+            if a.is_sign_negative() && !b.is_sign_negative() {
+                distance -= 1;
+            }
+            distance as f64 <= eps
+        }
+    }
+}
+
+/// Returns the `order_key` of `x` as an integer.
+fn key<T: Float>(x: T) -> u128 {
+    assert!(T::Bits::BYTES <= 16, "ULP distances need an order key of at most 128 bits");
+    let bits = x.order_key();
+    (0..T::Bits::BYTES).fold(0, |key, i| key << 8 | bits.key_byte(i) as u128)
+}
@@ -2,10 +2,52 @@
 // (C) 2015 Viktor Dahl <pazaconyoman@gmail.com>
 // This file is licensed under the same terms as Rust itself.
 
+use core::cmp::Ordering::*;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use super::sort::{sort_by};
-use unreachable::unreachable;
+#[cfg(feature = "alloc")]
+use super::sort::apply_permutation;
+use super::radix::RadixKey;
+#[cfg(feature = "alloc")]
+use super::radix::{lsd_radix_sort, RADIX_SORT_THRESHOLD};
 
 /// Minimal trait required for sorting floats.
+///
+/// This is implemented for `f32`, `f64`, `F16` and `Bf16`. Other number types
+/// can implement it to be sorted with `sort_floats` and the rest of the float
+/// functions. `PartialOrd` must be the usual order of the numbers, where NaNs
+/// are unordered and `-0` equals `+0`, and `order_key` must be consistent
+/// with it. If an implementation breaks these rules, the float functions may
+/// leave the numbers in an unspecified order, but are still memory safe.
+///
+/// # Example
+///
+/// A fixed point number with 16 fractional bits, which has no NaNs and only
+/// one zero:
+///
+///     use quickersort::Float;
+///
+///     #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+///     struct Fixed(i32);
+///
+///     impl Float for Fixed {
+///         fn one() -> Fixed { Fixed(1 << 16) }
+///         fn zero() -> Fixed { Fixed(0) }
+///         fn neg_zero() -> Fixed { Fixed(0) }
+///         fn is_nan(self) -> bool { false }
+///         fn is_sign_negative(self) -> bool { self.0 < 0 }
+///
+///         type Bits = u32;
+///
+///         fn order_key(self) -> u32 {
+///             self.0 as u32 ^ (1 << 31)
+///         }
+///     }
+///
+///     let mut v = [Fixed(3 << 16), Fixed(-1), Fixed(0)];
+///     quickersort::sort_floats(&mut v);
+///     assert_eq!(v, [Fixed(-1), Fixed(0), Fixed(3 << 16)]);
 pub trait Float: Copy + PartialOrd {
     /// Returns `1.0`.
     fn one() -> Self;
@@ -19,8 +61,31 @@ pub trait Float: Copy + PartialOrd {
     /// Returns `true` if this value is `NaN` and `false` otherwise.
     fn is_nan(self) -> bool;
 
-    /// Returns `true` if this value is positive, including `-0.0` and `-inf`.
+    /// Returns `true` if this value is positive or negative infinity and
+    /// `false` otherwise.
+    ///
+    /// The default is for types without infinities, and always returns
+    /// `false`.
+    #[inline]
+    fn is_infinite(self) -> bool {
+        false
+    }
+
+    /// Returns `true` if the sign bit of this value is set, including for
+    /// `-0.0`, `-inf` and negative NaNs.
     fn is_sign_negative(self) -> bool;
+
+    /// An unsigned integer type of the same width, used for `order_key`.
+    type Bits: RadixKey;
+
+    /// Returns an integer that orders like this value, such as its bits with
+    /// the sign bit flipped for positive numbers and all bits flipped for
+    /// negative ones.
+    ///
+    /// `-0.0` orders before `+0.0`, and `NaN`s order before `-inf` or after
+    /// `+inf` depending on their sign bit. The radix sorts and `Total` use
+    /// this.
+    fn order_key(self) -> Self::Bits;
 }
 
 impl Float for f32 {
@@ -35,12 +100,27 @@ impl Float for f32 {
 
     #[inline]
     fn is_nan(self) -> bool {
-        self != self
+        f32::is_nan(self)
+    }
+
+    #[inline]
+    fn is_infinite(self) -> bool {
+        f32::is_infinite(self)
     }
 
     #[inline]
     fn is_sign_negative(self) -> bool {
-        self < 0.0 || (1.0 / self) == ::core::f32::NEG_INFINITY
+        f32::is_sign_negative(self)
+    }
+
+    type Bits = u32;
+
+    #[inline]
+    fn order_key(self) -> u32 {
+        // Flip every bit of negative numbers, and only the sign bit of
+        // positive ones.
+        let bits = self.to_bits();
+        bits ^ (((bits as i32 >> 31) as u32) | (1 << 31))
     }
 }
 
@@ -56,11 +136,25 @@ impl Float for f64 {
 
     #[inline]
     fn is_nan(self) -> bool {
-        self != self
+        f64::is_nan(self)
     }
+
+    #[inline]
+    fn is_infinite(self) -> bool {
+        f64::is_infinite(self)
+    }
+
     #[inline]
     fn is_sign_negative(self) -> bool {
-        self < 0.0 || (1.0 / self) == ::core::f64::NEG_INFINITY
+        f64::is_sign_negative(self)
+    }
+
+    type Bits = u64;
+
+    #[inline]
+    fn order_key(self) -> u64 {
+        let bits = self.to_bits();
+        bits ^ (((bits as i64 >> 63) as u64) | (1 << 63))
     }
 }
 
@@ -68,47 +162,276 @@ impl Float for f64 {
 /// The ordering used is
 /// | -inf | < 0 | -0 | +0 | > 0 | +inf | NaN |
 pub fn sort_floats<T: Float>(v: &mut [T]) {
+    sort_floats_with(v, FloatOrder::default());
+}
+
+/// The direction in which `sort_floats_with` sorts numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Smallest first.
+    Ascending,
+    /// Largest first.
+    Descending,
+}
+
+/// Where `sort_floats_with` puts NaNs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NanPlacement {
+    /// All NaNs go before the numbers.
+    First,
+    /// All NaNs go after the numbers.
+    Last,
+    /// As in IEEE 754 `totalOrder`: NaNs with the sign bit set go below
+    /// `-inf` and the others above `+inf`, and NaNs are ordered by their
+    /// payload. For a descending sort, this order is reversed.
+    BySign,
+}
+
+/// The order in which `sort_floats_with` sorts floating point numbers.
+///
+/// The default is the order used by `sort_floats`:
+/// | -inf | < 0 | -0 | +0 | > 0 | +inf | NaN |
+///
+/// # Example
+///
+///     use quickersort::{Direction, FloatOrder};
+///
+///     let descending = FloatOrder { direction: Direction::Descending, ..FloatOrder::default() };
+///     let mut v = [1.0, ::std::f64::NAN, -0.0, 2.0, 0.0];
+///     ::quickersort::sort_floats_with(&mut v, descending);
+///     assert_eq!(v[..4], [2.0, 1.0, 0.0, -0.0]);
+///     assert!(v[2].is_sign_positive() && v[3].is_sign_negative());
+///     assert!(v[4].is_nan());
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FloatOrder {
+    /// The direction in which numbers are sorted.
+    pub direction: Direction,
+    /// Where NaNs go.
+    pub nans: NanPlacement,
+    /// Whether `-0` sorts before `+0` (or after, when descending). If this is
+    /// `false`, zeros are treated as equal and end up in any order.
+    pub signed_zeros: bool,
+}
+
+impl FloatOrder {
+    /// The IEEE 754-2008 `totalOrder` predicate:
+    /// | -NaN | -inf | < 0 | -0 | +0 | > 0 | +inf | +NaN |
+    /// where NaNs are also ordered by their payload.
+    pub const TOTAL_ORDER: FloatOrder = FloatOrder {
+        direction: Direction::Ascending,
+        nans: NanPlacement::BySign,
+        signed_zeros: true,
+    };
+}
+
+impl Default for FloatOrder {
+    fn default() -> FloatOrder {
+        FloatOrder {
+            direction: Direction::Ascending,
+            nans: NanPlacement::Last,
+            signed_zeros: true,
+        }
+    }
+}
+
+/// Sorts floating point numbers in the given order.
+///
+/// Like `sort_floats`, this moves the NaNs out of the way first, so the
+/// numbers are sorted with plain comparisons.
+///
+/// # Example
+///
+///     use quickersort::{FloatOrder, NanPlacement};
+///
+///     let nans_first = FloatOrder { nans: NanPlacement::First, ..FloatOrder::default() };
+///     let mut v = [3.0f32, ::std::f32::NAN, 1.0];
+///     ::quickersort::sort_floats_with(&mut v, nans_first);
+///     assert!(v[0].is_nan());
+///     assert_eq!(v[1..], [1.0, 3.0]);
+pub fn sort_floats_with<T: Float>(v: &mut [T], order: FloatOrder) {
     /*
      * We don't have hardware support for a total order on floats. NaN is not
      * smaller or greater than any number. We want NaNs to be last, so we could
      * just use is_nan() in the comparison function. It turns out that hurts
      * performance a lot, and in most cases we probably don't have any NaNs anyway.
      * 
-     * The solution is to first move all NaNs to the end of the array, and the
-     * sort the remainder with efficient comparisons. After sorting, the zeros
-     * might be in the wrong order, since -0 and 0 compare equal, but we want
-     * -0 to be sorted before 0. We binary search to find the zero interval fix
-     * them up.
+     * The solution is to first move all NaNs to the end (or the front) of the
+     * array, and then sort the remainder with efficient comparisons. After
+     * sorting, the zeros might be in the wrong order, since -0 and 0 compare
+     * equal, but we want -0 to be sorted before 0. We binary search to find
+     * the zero interval fix them up.
      */
 
     if v.len() <= 1 {
         return;
     }
+    let descending = order.direction == Direction::Descending;
+
+    // First we move the NaNs to the front or the end
+    let (start, end) = match order.nans {
+        NanPlacement::First => (move_to_front(v, |x: &T| x.is_nan()), v.len()),
+        NanPlacement::Last => (0, move_to_end(v, |x: &T| x.is_nan())),
+        NanPlacement::BySign => {
+            // The NaNs that go first are the negative ones, or the positive
+            // ones when descending.
+            let end = move_to_end(v, |x: &T| x.is_nan() && x.is_sign_negative() == descending);
+            (move_to_front(&mut v[..end], |x: &T| x.is_nan()), end)
+        }
+    };
 
-    // First we move all NaNs to the end
-    let mut rnan = v.len() - 1;
-    // Skip NaNs already in place
-    while rnan > 0 && v[rnan].is_nan() {
-        rnan -= 1;
+    // Sort the numbers with efficient comparisons
+    let numbers = &mut v[start..end];
+    if descending {
+        sort_by(numbers, &|x: &T, y: &T| y.partial_cmp(x).unwrap_or(Equal));
+    } else {
+        sort_by(numbers, &|x: &T, y: &T| x.partial_cmp(y).unwrap_or(Equal));
+    }
+    if order.signed_zeros {
+        fix_zeros(numbers, descending);
     }
-    let mut p = rnan;
-    while p > 0 {
-        p -= 1;
-        if v[p].is_nan() {
-            v.swap(p, rnan);
-            rnan -= 1;
+
+    if order.nans == NanPlacement::BySign {
+        let (front, back) = v.split_at_mut(end);
+        for nans in [&mut front[..start], back] {
+            if descending {
+                sort_by(nans, &|x: &T, y: &T| y.order_key().cmp(&x.order_key()));
+            } else {
+                sort_by(nans, &|x: &T, y: &T| x.order_key().cmp(&y.order_key()));
+            }
         }
     }
+}
+
+/// Sorts by a floating point key, in the order used by `sort_floats`.
+///
+/// Like `sort_floats`, this first moves the elements with a NaN key to the
+/// end, so the rest can be sorted with plain comparisons, and then puts the
+/// elements with a `-0` key before those with a `+0` key. `key` is called
+/// for every comparison; see `stable_sort_by_float_key` to call it only
+/// once per element.
+///
+/// # Example
+///
+///     struct Hit { score: f64, id: u32 }
+///
+///     let mut v = vec![Hit { score: 0.5, id: 0 }, Hit { score: ::std::f64::NAN, id: 1 },
+///                      Hit { score: -1.0, id: 2 }];
+///     ::quickersort::sort_by_float_key(&mut v, |h| h.score);
+///     let ids: Vec<_> = v.iter().map(|h| h.id).collect();
+///     assert_eq!(ids, [2, 0, 1]);
+pub fn sort_by_float_key<T, K: Float, F: Fn(&T) -> K>(v: &mut [T], key: F) {
+    if v.len() <= 1 {
+        return;
+    }
+    let end = move_to_end(v, |x| key(x).is_nan());
+    let numbers = &mut v[..end];
+    // Unlike in `sort_floats`, `key` might not return the same thing every
+    // time, so a NaN can still show up here.
+    sort_by(numbers, &|x: &T, y: &T| key(x).partial_cmp(&key(y)).unwrap_or(Equal));
+
+    let left = numbers.partition_point(|x| key(x) < K::zero());
+    let right = left + numbers[left..].partition_point(|x| key(x) == K::zero());
+    move_to_front(&mut numbers[left..right], |x| key(x).is_sign_negative());
+}
+
+/// Sorts stably by a floating point key, in the order used by `sort_floats`.
+///
+/// The key of every element is computed once, and the keys are sorted along
+/// with the position of their element, like `sort_by_float_key` does, with
+/// ties broken by position. The elements are then swapped into place.
+///
+/// # Example
+///
+///     let mut v = [(1.0, 'a'), (-0.0, 'b'), (1.0, 'c'), (0.0, 'd'), (-0.0, 'e')];
+///     ::quickersort::stable_sort_by_float_key(&mut v, |x| x.0);
+///     let order: Vec<_> = v.iter().map(|x| x.1).collect();
+///     assert_eq!(order, ['b', 'e', 'd', 'a', 'c']);
+#[cfg(feature = "alloc")]
+pub fn stable_sort_by_float_key<T, K: Float, F: Fn(&T) -> K>(v: &mut [T], key: F) {
+    if v.len() <= 1 {
+        return;
+    }
+    let mut keys: Vec<(K, usize)> = v.iter().enumerate().map(|(i, x)| (key(x), i)).collect();
+    sort_positions(&mut keys, |k| k.0, |k| k.1);
+
+    apply_permutation(v, &mut keys, |k| &mut k.1);
+}
+
+/// Returns the positions of the elements of `v` in the order used by
+/// `sort_floats`, leaving `v` as it is.
+///
+/// Equal numbers, and NaNs, are kept in the order of their positions, so
+/// the result is the same as that of a stable sort. See
+/// `argsort_floats_into` to write the positions into an existing slice.
+///
+/// # Example
+///
+///     let prices = [3.5, ::std::f64::NAN, -1.0, 0.0, -0.0, 3.5];
+///     let names = ["a", "b", "c", "d", "e", "f"];
+///     let order = ::quickersort::argsort_floats(&prices);
+///     assert_eq!(order, [2, 4, 3, 0, 5, 1]);
+///     let sorted_names: Vec<_> = order.iter().map(|&i| names[i]).collect();
+///     assert_eq!(sorted_names, ["c", "e", "d", "a", "f", "b"]);
+#[cfg(feature = "alloc")]
+pub fn argsort_floats<T: Float>(v: &[T]) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..v.len()).collect();
+    sort_positions(&mut indices, |&i| v[i], |&i| i);
+    indices
+}
+
+/// Writes the positions of the elements of `v` into `indices`, in the order
+/// used by `sort_floats`, leaving `v` as it is.
+///
+/// This is `argsort_floats` without allocating. Like `sort_floats`, it
+/// moves the positions of NaNs to the end first, so the rest can be sorted
+/// with plain comparisons.
+///
+/// # Panics
+///
+/// Panics if `indices` is not as long as `v`.
+///
+/// # Example
+///
+///     let v = [2.0f32, -0.0, 1.0, 0.0];
+///     let mut indices = [0; 4];
+///     ::quickersort::argsort_floats_into(&v, &mut indices);
+///     assert_eq!(indices, [1, 3, 2, 0]);
+pub fn argsort_floats_into<T: Float>(v: &[T], indices: &mut [usize]) {
+    assert_eq!(v.len(), indices.len(), "`indices` must be as long as `v`");
+    for (i, x) in indices.iter_mut().enumerate() {
+        *x = i;
+    }
+    sort_positions(indices, |&i| v[i], |&i| i);
+}
 
-    // Sort the non-NaN part with efficient comparisons
-    sort_by(&mut v[..rnan + 1], &|x: &T, y: &T|
-        match x.partial_cmp(y) {
+/// Sort elements that stand for a `key` at an `index` in the order used by
+/// `sort_floats`, with ties broken by `index`.
+fn sort_positions<P, K, F, I>(v: &mut [P], key: F, index: I)
+    where K: Float, F: Fn(&P) -> K, I: Fn(&P) -> usize
+{
+    let end = move_to_end(v, |p| key(p).is_nan());
+    let (numbers, nans) = v.split_at_mut(end);
+    sort_by(numbers, &|x: &P, y: &P|
+        match key(x).partial_cmp(&key(y)) {
+            Some(Equal) => index(x).cmp(&index(y)),
             Some(ord) => ord,
-            None      => unsafe { unreachable() }
+            None      => Equal,
         });
+    let left = numbers.partition_point(|p| key(p) < K::zero());
+    let right = left + numbers[left..].partition_point(|p| key(p) == K::zero());
+    sort_by(&mut numbers[left..right], &|x: &P, y: &P|
+        key(y).is_sign_negative().cmp(&key(x).is_sign_negative()).then(index(x).cmp(&index(y))));
+    sort_by(nans, &|x: &P, y: &P| index(x).cmp(&index(y)));
+}
 
-
-    let left = find_first_zero(&v[..rnan + 1]);
+/// Put the zeros in sorted `v` in order, with `-0` first unless
+/// `descending`.
+fn fix_zeros<T: Float>(v: &mut [T], descending: bool) {
+    let left = if descending {
+        v.partition_point(|&x| x > T::zero())
+    } else {
+        find_first_zero(v)
+    };
 
     // Count zeros of each type and then fill them in in the right order
     let mut zeros = 0;
@@ -123,23 +446,82 @@ pub fn sort_floats<T: Float>(v: &mut [T]) {
             zeros += 1;
         }
     }
-    for x in v[left..].iter_mut() {
-        if neg_zeros > 0 {
-            *x = Float::neg_zero();
-            neg_zeros -= 1;
-        } else if zeros > 0 {
-             *x = T::zero();
-             zeros -= 1;
+    let (first, first_count, second) = if descending {
+        (T::zero(), zeros, T::neg_zero())
+    } else {
+        (T::neg_zero(), neg_zeros, T::zero())
+    };
+    for (i, x) in v[left..left + zeros + neg_zeros].iter_mut().enumerate() {
+        *x = if i < first_count { first } else { second };
+    }
+}
+
+/// Sorts floating point numbers using a radix sort.
+///
+/// The ordering is the same as for `sort_floats`. The NaNs are moved to the
+/// end first, and the other numbers are then radix sorted by their
+/// `order_key`, as in `radix_sort`. This is about twice as fast as
+/// `sort_floats` on random `f64`s from 10000 to 100000 elements, and less so
+/// for larger slices. Slices shorter than 256 elements per byte of the type
+/// are sorted with `sort_floats` instead.
+///
+/// # Example
+///
+///     use std::f64::{INFINITY, NAN};
+///
+///     let mut v = vec![1.0, NAN, -0.0, -INFINITY, 0.0, -1.0];
+///     v.extend((0..2000).map(|i| i as f64));
+///     ::quickersort::radix_sort_floats(&mut v);
+///     assert_eq!(v[..5], [-INFINITY, -1.0, -0.0, 0.0, 0.0]);
+///     assert!(v[4].is_sign_positive() && v[2].is_sign_negative());
+///     assert!(v[v.len() - 1].is_nan());
+#[cfg(feature = "alloc")]
+pub fn radix_sort_floats<T: Float>(v: &mut [T]) {
+    if v.len() < RADIX_SORT_THRESHOLD * T::Bits::BYTES {
+        sort_floats(v);
+        return;
+    }
+    let end = move_to_end(v, |x: &T| x.is_nan());
+    lsd_radix_sort(&mut v[..end], &|x: &T| x.order_key());
+}
+
+/// Move the elements of `v` for which `pred` is true to the end, and return
+/// how many elements are left in front of them.
+pub(crate) fn move_to_end<T, P: Fn(&T) -> bool>(v: &mut [T], pred: P) -> usize {
+    let mut end = v.len();
+    let mut i = 0;
+    while i < end {
+        if pred(&v[i]) {
+            end -= 1;
+            v.swap(i, end);
+        } else {
+            i += 1;
+        }
+    }
+    end
+}
+
+/// Move the elements of `v` for which `pred` is true to the front, and
+/// return how many there are.
+pub(crate) fn move_to_front<T, P: Fn(&T) -> bool>(v: &mut [T], pred: P) -> usize {
+    let mut start = 0;
+    let mut i = v.len();
+    while i > start {
+        if pred(&v[i - 1]) {
+            v.swap(start, i - 1);
+            start += 1;
         } else {
-            break;
+            i -= 1;
         }
     }
+    start
 }
 
 /// Find the first zero in `v`.
-/// If there is no zero, it return v.len() - 1
-fn find_first_zero<T: Float>(v: &[T]) -> usize {
-    if v.len() == 0 { return 0; }
+/// If there is no zero, it returns the position of the first positive number,
+/// or v.len()
+pub(crate) fn find_first_zero<T: Float>(v: &[T]) -> usize {
+    if v.is_empty() { return 0; }
     let mut hi = v.len() - 1;
     let mut left = 0;
 
@@ -155,5 +537,5 @@ fn find_first_zero<T: Float>(v: &[T]) -> usize {
     while left < v.len() && v[left] < T::zero() {
         left += 1;
     }
-    return left;
+    left
 }
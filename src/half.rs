@@ -0,0 +1,211 @@
+// This file is licensed under the same terms as Rust itself.
+
+use core::cmp::Ordering;
+use core::fmt;
+use super::float::Float;
+
+/// An IEEE 754 half precision floating point number, for storage.
+///
+/// This only converts to and from `f32` and implements `Float`, so slices of
+/// it can be sorted with `sort_floats` and the other float sorts.
+///
+/// # Example
+///
+///     use quickersort::F16;
+///
+///     let mut v: Vec<F16> = [0.5, -2.0, 65504.0].iter().map(|&x| F16::from_f32(x)).collect();
+///     quickersort::sort_floats(&mut v);
+///     let v: Vec<f32> = v.into_iter().map(F16::to_f32).collect();
+///     assert_eq!(v, [-2.0, 0.5, 65504.0]);
+#[derive(Clone, Copy, Default)]
+pub struct F16(u16);
+
+/// A bfloat16 floating point number, for storage.
+///
+/// bfloat16 has the same exponent range as `f32`, with only 8 bits of
+/// precision. Like `F16`, it only converts to and from `f32` and implements
+/// `Float`.
+#[derive(Clone, Copy, Default)]
+pub struct Bf16(u16);
+
+impl F16 {
+    /// Creates a number from its raw bits.
+    pub fn from_bits(bits: u16) -> F16 {
+        F16(bits)
+    }
+
+    /// Returns the raw bits of this number.
+    pub fn to_bits(self) -> u16 {
+        self.0
+    }
+
+    /// Converts an `f32`, rounding to the nearest number, ties to even.
+    pub fn from_f32(x: f32) -> F16 {
+        let x = x.to_bits();
+        let sign = (x >> 16) as u16 & 0x8000;
+        let exp = (x >> 23) & 0xff;
+        let man = x & 0x7f_ffff;
+        if exp == 0xff {
+            // Infinity, or NaN with the quiet bit set to keep it a NaN.
+            let nan = if man == 0 { 0 } else { 0x200 | (man >> 13) as u16 };
+            return F16(sign | 0x7c00 | nan);
+        }
+        let half_exp = exp as i32 - 127 + 15;
+        if half_exp >= 0x1f {
+            return F16(sign | 0x7c00);
+        }
+        if half_exp <= 0 {
+            // Subnormal, or too small even for that.
+            if half_exp < -10 {
+                return F16(sign);
+            }
+            let man = man | 0x80_0000;
+            let shift = (14 - half_exp) as u32;
+            let half_man = round_shift(man, shift);
+            return F16(sign | half_man as u16);
+        }
+        // Rounding up may carry into the exponent, which is still correct.
+        F16(sign | round_shift(((half_exp as u32) << 23) | man, 13) as u16)
+    }
+
+    /// Converts to an `f32`, which is exact.
+    pub fn to_f32(self) -> f32 {
+        let sign = ((self.0 & 0x8000) as u32) << 16;
+        let exp = ((self.0 >> 10) & 0x1f) as u32;
+        let man = (self.0 & 0x3ff) as u32;
+        let bits = match (exp, man) {
+            (0, 0) => sign,
+            (0, _) => {
+                // Subnormal: shift the leading one into the implicit bit.
+                let shift = man.leading_zeros() - 21;
+                sign | ((127 - 15 + 1 - shift) << 23) | ((man << shift) & 0x3ff) << 13
+            }
+            (0x1f, 0) => sign | 0x7f80_0000,
+            (0x1f, _) => sign | 0x7fc0_0000 | (man << 13),
+            _ => sign | ((exp + 127 - 15) << 23) | (man << 13),
+        };
+        f32::from_bits(bits)
+    }
+}
+
+impl Bf16 {
+    /// Creates a number from its raw bits.
+    pub fn from_bits(bits: u16) -> Bf16 {
+        Bf16(bits)
+    }
+
+    /// Returns the raw bits of this number.
+    pub fn to_bits(self) -> u16 {
+        self.0
+    }
+
+    /// Converts an `f32`, rounding to the nearest number, ties to even.
+    pub fn from_f32(x: f32) -> Bf16 {
+        let x = x.to_bits();
+        if x & 0x7fff_ffff > 0x7f80_0000 {
+            // Set the quiet bit, so the NaN stays a NaN.
+            return Bf16((x >> 16) as u16 | 0x40);
+        }
+        Bf16(round_shift(x, 16) as u16)
+    }
+
+    /// Converts to an `f32`, which is exact.
+    pub fn to_f32(self) -> f32 {
+        f32::from_bits((self.0 as u32) << 16)
+    }
+}
+
+/// Returns `x >> shift`, rounded to the nearest integer, ties to even.
+fn round_shift(x: u32, shift: u32) -> u32 {
+    let half = 1 << (shift - 1);
+    let rest = x & ((half << 1) - 1);
+    let y = x >> shift;
+    if rest > half || (rest == half && y & 1 == 1) {
+        y + 1
+    } else {
+        y
+    }
+}
+
+macro_rules! half_float {
+    ($t:ident, $one:expr, $exp_mask:expr) => {
+        impl From<$t> for f32 {
+            fn from(x: $t) -> f32 {
+                x.to_f32()
+            }
+        }
+
+        impl From<$t> for f64 {
+            fn from(x: $t) -> f64 {
+                x.to_f32() as f64
+            }
+        }
+
+        impl fmt::Debug for $t {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.to_f32().fmt(f)
+            }
+        }
+
+        impl fmt::Display for $t {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.to_f32().fmt(f)
+            }
+        }
+
+        impl PartialEq for $t {
+            fn eq(&self, other: &$t) -> bool {
+                self.partial_cmp(other) == Some(Ordering::Equal)
+            }
+        }
+
+        impl PartialOrd for $t {
+            fn partial_cmp(&self, other: &$t) -> Option<Ordering> {
+                if self.is_nan() || other.is_nan() {
+                    None
+                } else if (self.0 | other.0) & 0x7fff == 0 {
+                    // `-0` and `+0` are equal.
+                    Some(Ordering::Equal)
+                } else {
+                    Some(self.order_key().cmp(&other.order_key()))
+                }
+            }
+        }
+
+        impl Float for $t {
+            #[inline]
+            fn one() -> $t { $t($one) }
+
+            #[inline]
+            fn zero() -> $t { $t(0) }
+
+            #[inline]
+            fn neg_zero() -> $t { $t(0x8000) }
+
+            #[inline]
+            fn is_nan(self) -> bool {
+                self.0 & 0x7fff > $exp_mask
+            }
+
+            #[inline]
+            fn is_infinite(self) -> bool {
+                self.0 & 0x7fff == $exp_mask
+            }
+
+            #[inline]
+            fn is_sign_negative(self) -> bool {
+                self.0 & 0x8000 != 0
+            }
+
+            type Bits = u16;
+
+            #[inline]
+            fn order_key(self) -> u16 {
+                self.0 ^ ((((self.0 as i16) >> 15) as u16) | 0x8000)
+            }
+        }
+    }
+}
+
+half_float!(F16, 0x3c00, 0x7c00);
+half_float!(Bf16, 0x3f80, 0x7f80);
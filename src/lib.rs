@@ -28,7 +28,17 @@
 //! }
 //! ```
 //!
+//! `sort_by_mut` and the other `_mut` functions take `FnMut` functions
+//! instead. `try_sort_by` and `try_sort_by_key` take functions that return a
+//! `Result`. If the "alloc" feature is enabled, `checked_sort_by` returns an
+//! error if the comparison function is not a total order.
+//!
 //! Unlike the standard library sort function, introsort is _not_ a stable sort.
+//! `stable_sort_in_place` is a stable block merge sort that, like `sort`, does
+//! not allocate. If the "alloc" feature is enabled, the crate also includes
+//! `stable_sort`, a faster adaptive merge sort that allocates a buffer.
+//! `stable_sort_by_with_buffer` runs the same merge sort in a buffer provided
+//! by the caller.
 //!
 //! ## Details ##
 //! At its heart, it is a dual-pivot quicksort. For partition with many equal
@@ -39,6 +49,31 @@
 //!
 //! Unlike the `std` sort, it does not allocate.
 //!
+//! If the "parallel" feature is enabled, `par_sort` hands the partitions to
+//! other threads after each partitioning step, and `par_stable_sort` sorts
+//! chunks of the slice on separate threads and merges them in parallel. The
+//! "parallel" feature needs Rust 1.63 or later, for `std::thread::scope`.
+//!
+//! ## Radix sort ##
+//! If the "alloc" feature is enabled, `radix_sort` sorts primitive integers
+//! without comparing them, by moving them into a buffer and back once for
+//! every byte that is not the same in all of them. `radix_sort_by_key` does
+//! the same for any type with a key that implements `RadixKey`, and is
+//! stable.
+//!
+//! `sort_points_morton` and `sort_points_hilbert` sort 2D and 3D points
+//! along a Z-order or Hilbert curve.
+//!
+//! ## Options ##
+//! `sort_options` sorts a slice of `Option`s with the `None`s first or last.
+//!
+//! ## Selection ##
+//! `select_nth` uses the same partitioning as `sort`, but only follows the
+//! partition that contains the requested position, so finding a median or a
+//! percentile takes linear time on average. `partial_sort` builds on it to
+//! sort only the first `k` positions of a slice, and `sort_range` sorts only
+//! the positions in a given range, skipping partitions that fall outside it.
+//!
 //! ## Performance ##
 //! It is quite fast, outperforming the standard sort on all data sets I have
 //! tried. The performance difference varies depending on the characteristics of
@@ -58,15 +93,103 @@
 //! ``` | -inf | < 0 | -0 | +0 | > 0 | +inf | NaN | ```
 //!
 //! `sort_floats` is much more efficient than passing a comparator function
-//! implementing this ordering to `sort_by`.
+//! implementing this ordering to `sort_by`. If the "alloc" feature is
+//! enabled, `radix_sort_floats` sorts into the same order with a radix sort,
+//! which is faster still for large slices.
+//!
+//! `sort_floats_with` sorts in other orders, described by a `FloatOrder`:
+//! descending, with NaNs first, with `-0` and `+0` treated as equal, or
+//! following the IEEE 754 `totalOrder` predicate.
+//!
+//! To sort records by a floating point field, use `sort_by_float_key`, or
+//! `stable_sort_by_float_key` if the "alloc" feature is enabled. They sort
+//! into the same order as `sort_floats`, using the same strategy.
+//! `argsort_floats_into` leaves the numbers where they are and returns the
+//! order as positions instead, to reorder other columns by one column of
+//! floats; `argsort_floats` allocates the positions for it.
+//!
+//! After sorting, `cluster_floats` finds the runs of numbers that are within
+//! a tolerance of each other, measured absolutely or in units in the last
+//! place, and `dedup_floats_with_tolerance` keeps one number from each.
+//!
+//! `float_lower_bound`, `float_upper_bound`, `float_equal_range` and
+//! `float_range` binary search a slice sorted by `sort_floats` in the same
+//! order, unlike `binary_search_by` with `partial_cmp`, which gets lost
+//! around NaNs and zeros. `SortedFloatsView` finds the regions of negative
+//! numbers, zeros, positive numbers, infinities and NaNs once, for repeated
+//! searches.
+//!
+//! `TotalF32` and `TotalF64` wrap floats to implement `Ord`, `Eq` and `Hash`
+//! in the same order, so they can be used as keys in maps and heaps. Slices of
+//! floats can be viewed as slices of them without copying.
+//!
+//! All of these work on any type that implements the `Float` trait. Besides
+//! `f32` and `f64`, the crate includes `F16` and `Bf16`, half precision types
+//! for storage that convert to and from `f32`.
+//!
+//! `sort_partial_by` sorts any `PartialOrd` type the same way, with a policy
+//! for elements like NaNs that are incomparable.
+//!
+//! The `stats` module computes medians and quantiles of floats, using
+//! selection instead of sorting.
 
 #![no_std]
 
-extern crate unreachable;
 extern crate nodrop;
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 pub use sort::{sort, sort_by, sort_by_key, insertion_sort, heapsort};
-pub use float::{sort_floats};
+pub use sort::{sort_by_mut, sort_by_key_mut, insertion_sort_mut, heapsort_mut};
+pub use sort::{try_sort_by, try_sort_by_key};
+pub use sort::{select_nth, select_nth_by, select_nth_by_key};
+pub use sort::{partial_sort, partial_sort_by, partial_sort_by_key};
+pub use sort::{sort_range, sort_range_by, sort_range_by_key};
+pub use sort::{stable_sort_in_place, stable_sort_in_place_by, stable_sort_in_place_by_key};
+pub use float::{Float, sort_floats, sort_floats_with, FloatOrder, Direction, NanPlacement};
+pub use float::{sort_by_float_key, argsort_floats_into};
+pub use total::{Total, TotalF32, TotalF64};
+pub use half::{F16, Bf16};
+pub use search::{float_lower_bound, float_upper_bound, float_equal_range, float_range, SortedFloatsView};
+pub use dedup::{cluster_floats, dedup_floats_with_tolerance, ToleranceMode, Clusters};
+pub use curve::{CurvePoint, BoundingBox};
+pub use options::{sort_options, sort_options_by, sort_options_by_key, NullsPlacement};
+pub use partial::{sort_partial, sort_partial_by, IncomparablePolicy, IncomparableError};
+pub use checked::{ComparatorError, ComparatorErrorKind};
+#[cfg(feature = "alloc")]
+pub use curve::{sort_points_morton, sort_points_morton_with, sort_points_hilbert, sort_points_hilbert_with};
+#[cfg(feature = "alloc")]
+pub use float::{radix_sort_floats, stable_sort_by_float_key, argsort_floats};
+#[cfg(feature = "alloc")]
+pub use checked::{checked_sort, checked_sort_by, checked_sort_by_key};
+pub use merge::{stable_sort_by_with_buffer};
+pub use radix::{RadixKey};
+#[cfg(feature = "alloc")]
+pub use radix::{radix_sort, radix_sort_by_key};
+#[cfg(feature = "alloc")]
+pub use merge::{stable_sort, stable_sort_by, stable_sort_by_key};
+#[cfg(feature = "parallel")]
+pub use par::{par_sort, par_sort_by, par_sort_by_key, par_sort_by_with_min_len};
+#[cfg(feature = "parallel")]
+pub use par::{par_stable_sort, par_stable_sort_by, par_stable_sort_by_key, par_stable_sort_by_with_min_len};
+#[cfg(feature = "parallel")]
+#[doc(hidden)]
+pub use par::{par_sort_by_with_threads, par_stable_sort_by_with_threads};
 
 mod sort;
 mod float;
+mod merge;
+mod radix;
+mod total;
+mod half;
+mod dedup;
+mod search;
+mod curve;
+mod partial;
+mod options;
+mod checked;
+pub mod stats;
+#[cfg(feature = "parallel")]
+mod par;
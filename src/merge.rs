@@ -0,0 +1,341 @@
+// This file is licensed under the same terms as Rust itself.
+
+use core::cmp::Ordering;
+use core::cmp::Ordering::*;
+use core::cmp::min;
+use core::mem::{size_of, MaybeUninit};
+use core::ptr;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use super::sort::insertion_sort;
+
+/// The maximum number of elements to be insertion sorted without looking
+/// for runs.
+const MAX_INSERTION_SORT_ELEMS: usize = 20;
+
+/// Runs shorter than this are extended with an insertion sort.
+const MIN_RUN: usize = 10;
+
+/// Upper bound on the number of pending runs.
+/// The merge invariants make run lengths grow at least as fast as the
+/// Fibonacci numbers, so this is never reached for any slice that fits in
+/// memory.
+const MAX_RUNS: usize = 128;
+
+/// Sort stably using a comparison function.
+///
+/// Elements that compare equal keep their relative order. This allocates a
+/// buffer of half the slice's length; see `stable_sort_by_with_buffer` to
+/// provide the buffer yourself.
+///
+/// # Example
+///
+///     let mut v = [(1, 'c'), (0, 'b'), (1, 'a'), (0, 'd')];
+///     ::quickersort::stable_sort_by(&mut v, &|a, b| a.0.cmp(&b.0));
+///     assert_eq!(v, [(0, 'b'), (0, 'd'), (1, 'c'), (1, 'a')]);
+#[cfg(feature = "alloc")]
+pub fn stable_sort_by<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C) {
+    if size_of::<T>() == 0 {
+        return;
+    }
+    if v.len() <= MAX_INSERTION_SORT_ELEMS {
+        insertion_sort(v, compare);
+        return;
+    }
+    let buf_len = v.len() / 2;
+    let mut buf: Vec<T> = Vec::with_capacity(buf_len);
+    unsafe {
+        merge_sort(v, buf.as_mut_ptr(), buf_len, compare);
+    }
+}
+
+/// Sort stably using a conversion function.
+///
+/// See `stable_sort_by` for details.
+#[cfg(feature = "alloc")]
+pub fn stable_sort_by_key<T, K: Ord, F: Fn(&T) -> K>(v: &mut [T], key: F) {
+    stable_sort_by(v, &|a, b| key(a).cmp(&key(b)));
+}
+
+/// Sort stably using the default comparison function.
+///
+/// See `stable_sort_by` for details.
+#[cfg(feature = "alloc")]
+pub fn stable_sort<T: Ord>(v: &mut [T]) {
+    stable_sort_by(v, &|a, b| a.cmp(b));
+}
+
+/// Sort stably using a comparison function and a caller-provided buffer.
+///
+/// This is the same merge sort as `stable_sort_by`, but it never allocates.
+/// A `scratch` buffer of `v.len() / 2` elements is enough to merge every run
+/// with it. Merges that don't fit into a smaller buffer are split up with
+/// rotations until the pieces do, which makes sorting slower the smaller the
+/// buffer is, down to O(n log(n)²) time with an empty buffer.
+///
+/// The contents of `scratch` are garbage afterwards, and no elements are ever
+/// left in it.
+///
+/// # Example
+///
+///     use std::mem::MaybeUninit;
+///
+///     let mut v = [(1, 'c'), (0, 'b'), (1, 'a'), (0, 'd')];
+///     let mut scratch = [MaybeUninit::uninit(); 1];
+///     ::quickersort::stable_sort_by_with_buffer(&mut v, &mut scratch, &|a, b| a.0.cmp(&b.0));
+///     assert_eq!(v, [(0, 'b'), (0, 'd'), (1, 'c'), (1, 'a')]);
+pub fn stable_sort_by_with_buffer<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], scratch: &mut [MaybeUninit<T>],
+                                                                compare: &C) {
+    if size_of::<T>() == 0 {
+        return;
+    }
+    if v.len() <= MAX_INSERTION_SORT_ELEMS {
+        insertion_sort(v, compare);
+        return;
+    }
+    let buf_len = min(scratch.len(), v.len() / 2);
+    unsafe {
+        merge_sort(v, scratch.as_mut_ptr() as *mut T, buf_len, compare);
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+/// An adaptive merge sort, in the style of TimSort.
+///
+/// The slice is scanned from the back for ascending or strictly descending
+/// runs. Short runs are extended with an insertion sort, and runs are merged
+/// while they violate the TimSort stack invariants.
+///
+/// `buf` must be valid for writes of `buf_len` elements.
+unsafe fn merge_sort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], buf: *mut T, buf_len: usize, compare: &C) {
+    let len = v.len();
+    let mut runs = [Run { start: 0, len: 0 }; MAX_RUNS];
+    let mut runs_len = 0;
+    let mut end = len;
+    while end > 0 {
+        let mut start = find_run(&mut v[..end], compare);
+        if end - start < MIN_RUN {
+            start = end.saturating_sub(MIN_RUN);
+            insertion_sort(&mut v[start..end], compare);
+        }
+        runs[runs_len] = Run { start, len: end - start };
+        runs_len += 1;
+        end = start;
+
+        while let Some(r) = collapse(&runs[..runs_len]) {
+            let left = runs[r + 1];
+            let right = runs[r];
+            merge(&mut v[left.start..right.start + right.len], left.len, buf, buf_len, compare);
+            runs[r] = Run { start: left.start, len: left.len + right.len };
+            for i in r + 1..runs_len - 1 {
+                runs[i] = runs[i + 1];
+            }
+            runs_len -= 1;
+        }
+    }
+    debug_assert!(runs_len == 1 && runs[0].start == 0 && runs[0].len == len);
+}
+
+/// Find the run at the end of `v`, reversing it if it is strictly descending.
+/// Returns the start of the run.
+fn find_run<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C) -> usize {
+    let end = v.len();
+    let mut start = end - 1;
+    if start > 0 {
+        start -= 1;
+        if compare(&v[start], &v[start + 1]) == Greater {
+            while start > 0 && compare(&v[start - 1], &v[start]) == Greater {
+                start -= 1;
+            }
+            v[start..end].reverse();
+        } else {
+            while start > 0 && compare(&v[start - 1], &v[start]) != Greater {
+                start -= 1;
+            }
+        }
+    }
+    start
+}
+
+/// Examine the run stack and return the index of the pair of runs that
+/// should be merged next, if any. The runs at `r` and `r + 1` are merged.
+///
+/// These are the TimSort invariants, including the fix for the bug found by
+/// de Gouw et al. in the original formulation. The last run is always merged
+/// down once the scan reaches the start of the slice.
+fn collapse(runs: &[Run]) -> Option<usize> {
+    let n = runs.len();
+    if n >= 2 && (runs[n - 1].start == 0
+                  || runs[n - 2].len <= runs[n - 1].len
+                  || (n >= 3 && runs[n - 3].len <= runs[n - 2].len + runs[n - 1].len)
+                  || (n >= 4 && runs[n - 4].len <= runs[n - 3].len + runs[n - 2].len)) {
+        if n >= 3 && runs[n - 3].len < runs[n - 1].len {
+            Some(n - 3)
+        } else {
+            Some(n - 2)
+        }
+    } else {
+        None
+    }
+}
+
+/// Merge the sorted runs `v[..mid]` and `v[mid..]`, using `buf` as temporary
+/// storage.
+///
+/// If the shorter run doesn't fit into `buf`, the merge is split into two
+/// smaller ones by rotating the middle part of the slice, like the
+/// `__merge_without_buffer` of the C++ standard library.
+///
+/// `buf` must be valid for writes of `buf_len` elements.
+unsafe fn merge<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], mid: usize, buf: *mut T, buf_len: usize,
+                                              compare: &C) {
+    let len = v.len();
+    if mid == 0 || mid == len || compare(v.get_unchecked(mid - 1), v.get_unchecked(mid)) != Greater {
+        // Already in order.
+        return;
+    }
+
+    if min(mid, len - mid) <= buf_len {
+        merge_with_buffer(v, mid, buf, compare);
+        return;
+    }
+
+    // Cut the longer run in half, and find where its middle element belongs
+    // in the other run. Equal elements from the right run stay after those
+    // from the left run.
+    let (left_cut, right_cut) = if mid >= len - mid {
+        let left_cut = mid / 2;
+        let right_cut = mid + lower_bound(&v[mid..], &v[left_cut], compare);
+        (left_cut, right_cut)
+    } else {
+        let right_cut = mid + (len - mid) / 2;
+        let left_cut = upper_bound(&v[..mid], &v[right_cut], compare);
+        (left_cut, right_cut)
+    };
+    v[left_cut..right_cut].rotate_left(mid - left_cut);
+    let new_mid = left_cut + (right_cut - mid);
+    merge(&mut v[..new_mid], left_cut, buf, buf_len, compare);
+    merge(&mut v[new_mid..], mid - left_cut, buf, buf_len, compare);
+}
+
+/// Merge the sorted runs `v[..mid]` and `v[mid..]`, moving the shorter one
+/// into `buf`.
+///
+/// `buf` must be valid for writes of `min(mid, v.len() - mid)` elements.
+unsafe fn merge_with_buffer<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], mid: usize, buf: *mut T, compare: &C) {
+    let len = v.len();
+    let v = v.as_mut_ptr();
+    let v_mid = v.add(mid);
+    let v_end = v.add(len);
+
+    // The shorter run is moved into `buf`, leaving a hole in `v`. The hole
+    // is filled in with the merged elements, and whatever is left in `buf`
+    // is moved into the hole by `MergeHole::drop`, even if `compare` unwinds.
+    if mid <= len - mid {
+        // Merge forwards, with the left run in `buf`.
+        ptr::copy_nonoverlapping(v, buf, mid);
+        let mut hole = MergeHole {
+            start: buf,
+            end: buf.add(mid),
+            dest: v,
+        };
+        let mut right = v_mid;
+        while hole.start < hole.end && right < v_end {
+            // Take from the right run only if it is strictly smaller, to
+            // keep equal elements in order.
+            let to_copy = if compare(&*right, &*hole.start) == Less {
+                get_and_increment(&mut right)
+            } else {
+                get_and_increment(&mut hole.start)
+            };
+            ptr::copy_nonoverlapping(to_copy, get_and_increment(&mut hole.dest), 1);
+        }
+        // hole dropped here
+    } else {
+        // Merge backwards, with the right run in `buf`.
+        ptr::copy_nonoverlapping(v_mid, buf, len - mid);
+        let mut hole = MergeHole {
+            start: buf,
+            end: buf.add(len - mid),
+            dest: v_mid,
+        };
+        let mut out = v_end;
+        while v < hole.dest && hole.start < hole.end {
+            // Take from the left run only if it is strictly greater, to
+            // keep equal elements in order.
+            let to_copy = if compare(&*hole.end.offset(-1), &*hole.dest.offset(-1)) == Less {
+                decrement_and_get(&mut hole.dest)
+            } else {
+                decrement_and_get(&mut hole.end)
+            };
+            ptr::copy_nonoverlapping(to_copy, decrement_and_get(&mut out), 1);
+        }
+        // hole dropped here
+    }
+}
+
+/// Index of the first element of the sorted slice `v` that is not less than
+/// `value`.
+fn lower_bound<T, C: Fn(&T, &T) -> Ordering>(v: &[T], value: &T, compare: &C) -> usize {
+    let mut lo = 0;
+    let mut hi = v.len();
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if compare(&v[mid], value) == Less {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// Index of the first element of the sorted slice `v` that is greater than
+/// `value`.
+fn upper_bound<T, C: Fn(&T, &T) -> Ordering>(v: &[T], value: &T, compare: &C) -> usize {
+    let mut lo = 0;
+    let mut hi = v.len();
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if compare(value, &v[mid]) == Less {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    lo
+}
+
+/// The part of a merge buffer that still has to be moved back into the slice.
+struct MergeHole<T> {
+    start: *mut T,
+    end: *mut T,
+    dest: *mut T,
+}
+
+impl<T> Drop for MergeHole<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let len = (self.end as usize - self.start as usize) / size_of::<T>();
+            ptr::copy_nonoverlapping(self.start, self.dest, len);
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn get_and_increment<T>(ptr: &mut *mut T) -> *mut T {
+    let old = *ptr;
+    *ptr = ptr.offset(1);
+    old
+}
+
+#[inline(always)]
+unsafe fn decrement_and_get<T>(ptr: &mut *mut T) -> *mut T {
+    *ptr = ptr.offset(-1);
+    *ptr
+}
@@ -0,0 +1,89 @@
+// This file is licensed under the same terms as Rust itself.
+
+use core::cmp::Ordering;
+use core::hint::unreachable_unchecked;
+use super::sort::sort_by;
+use super::float::{move_to_end, move_to_front};
+
+/// Where `sort_options` puts the `None`s, like SQL's `NULLS FIRST` and
+/// `NULLS LAST`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NullsPlacement {
+    /// Put the `None`s before the `Some`s.
+    First,
+    /// Put the `None`s after the `Some`s.
+    Last,
+}
+
+/// Sort optional values using a comparison function, with the `None`s
+/// placed as `nulls` says.
+///
+/// Like `sort_floats` does with NaNs, this first moves the `None`s to one
+/// end in a single pass, and then sorts the `Some`s without checking for
+/// `None` in every comparison.
+///
+/// # Example
+///
+///     use quickersort::NullsPlacement;
+///
+///     let mut v = [Some(1), None, Some(3), Some(2)];
+///     quickersort::sort_options_by(&mut v, NullsPlacement::Last, &|a, b| b.cmp(a));
+///     assert_eq!(v, [Some(3), Some(2), Some(1), None]);
+pub fn sort_options_by<T, C: Fn(&T, &T) -> Ordering>(v: &mut [Option<T>], nulls: NullsPlacement, compare: &C) {
+    let somes = match nulls {
+        NullsPlacement::First => {
+            let start = move_to_front(v, |x: &Option<T>| x.is_none());
+            &mut v[start..]
+        }
+        NullsPlacement::Last => {
+            let end = move_to_end(v, |x: &Option<T>| x.is_none());
+            &mut v[..end]
+        }
+    };
+    // `sort_by` only ever compares elements of `somes`, and only `Some`s are
+    // left in it.
+    sort_by(somes, &|x: &Option<T>, y: &Option<T>| unsafe { compare(unwrap_some(x), unwrap_some(y)) });
+}
+
+/// Returns the value in `x` without checking that it is a `Some`.
+///
+/// `x` must be a `Some`.
+#[inline(always)]
+unsafe fn unwrap_some<T>(x: &Option<T>) -> &T {
+    match *x {
+        Some(ref x) => x,
+        None => unreachable_unchecked(),
+    }
+}
+
+/// Sort optional values using a conversion function, with the `None`s
+/// placed as `nulls` says.
+///
+/// See `sort_options_by` for details.
+///
+/// # Example
+///
+///     use quickersort::NullsPlacement;
+///
+///     let mut v = [Some("ccc"), None, Some("a"), Some("bb")];
+///     quickersort::sort_options_by_key(&mut v, NullsPlacement::First, |s| s.len());
+///     assert_eq!(v, [None, Some("a"), Some("bb"), Some("ccc")]);
+pub fn sort_options_by_key<T, K: Ord, F: Fn(&T) -> K>(v: &mut [Option<T>], nulls: NullsPlacement, key: F) {
+    sort_options_by(v, nulls, &|a, b| key(a).cmp(&key(b)));
+}
+
+/// Sort optional values using the default comparison function, with the
+/// `None`s placed as `nulls` says.
+///
+/// See `sort_options_by` for details.
+///
+/// # Example
+///
+///     use quickersort::NullsPlacement;
+///
+///     let mut v = [Some(1), None, Some(-3), None];
+///     quickersort::sort_options(&mut v, NullsPlacement::Last);
+///     assert_eq!(v, [Some(-3), Some(1), None, None]);
+pub fn sort_options<T: Ord>(v: &mut [Option<T>], nulls: NullsPlacement) {
+    sort_options_by(v, nulls, &|a, b| a.cmp(b));
+}
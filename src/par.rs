@@ -0,0 +1,375 @@
+// This file is licensed under the same terms as Rust itself.
+
+use core::cmp::Ordering;
+use core::cmp::Ordering::*;
+use core::cmp::{min, max};
+use core::mem::{self, size_of, MaybeUninit};
+use core::ptr;
+use core::slice;
+use core::sync::atomic::AtomicUsize;
+use core::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+use alloc::vec::Vec;
+use std::thread::{self, Scope};
+use super::sort::{sort_by, introsort, heapsort_mut, maybe_insertion_sort, capped_insertion_sort, choose_pivots,
+                  fat_partition, log2, DualPivotSort};
+use super::merge::{stable_sort_by, stable_sort_by_with_buffer};
+
+/// Partitions shorter than this are sorted sequentially by `par_sort_by`.
+const DEFAULT_MIN_LEN: usize = 16 * 1024;
+
+/// Sort in parallel using a comparison function.
+///
+/// This is the same introsort as `sort_by`, but after each partitioning step
+/// the partitions are handed to other threads, up to the number of threads
+/// returned by `std::thread::available_parallelism`. Partitions shorter than
+/// 16384 elements are sorted sequentially; see `par_sort_by_with_min_len` to
+/// tune this.
+///
+/// # Example
+///
+///     let mut v: Vec<u32> = (0..100_000).rev().collect();
+///     ::quickersort::par_sort_by(&mut v, &|a, b| a.cmp(b));
+///     assert!(v.windows(2).all(|w| w[0] <= w[1]));
+pub fn par_sort_by<T: Send, C: Fn(&T, &T) -> Ordering + Sync>(v: &mut [T], compare: &C) {
+    par_sort_by_with_min_len(v, DEFAULT_MIN_LEN, compare);
+}
+
+/// Sort in parallel using a comparison function, sorting partitions shorter
+/// than `min_len` sequentially.
+///
+/// See `par_sort_by` for details.
+pub fn par_sort_by_with_min_len<T: Send, C: Fn(&T, &T) -> Ordering + Sync>(v: &mut [T], min_len: usize,
+                                                                           compare: &C) {
+    par_sort_by_with_threads(v, min_len, available_threads(), compare);
+}
+
+/// Like `par_sort_by_with_min_len`, but with up to `threads` threads instead
+/// of the number the machine has, so that the tests run the parallel code on
+/// any machine.
+#[doc(hidden)]
+pub fn par_sort_by_with_threads<T: Send, C: Fn(&T, &T) -> Ordering + Sync>(v: &mut [T], min_len: usize,
+                                                                           threads: usize, compare: &C) {
+    if v.len() <= min_len || threads <= 1 {
+        sort_by(v, compare);
+        return;
+    }
+    let heapsort_depth = (3 * log2(v.len())) / 2;
+    let idle = AtomicUsize::new(threads - 1);
+    thread::scope(|scope| {
+        par_introsort(scope, v, compare, 0, heapsort_depth, min_len, &idle);
+    });
+}
+
+/// Sort in parallel using a conversion function.
+///
+/// See `par_sort_by` for details.
+pub fn par_sort_by_key<T: Send, K: Ord, F: Fn(&T) -> K + Sync>(v: &mut [T], key: F) {
+    par_sort_by(v, &|a, b| key(a).cmp(&key(b)));
+}
+
+/// Sort in parallel using the default comparison function.
+///
+/// See `par_sort_by` for details.
+pub fn par_sort<T: Ord + Send>(v: &mut [T]) {
+    par_sort_by(v, &|a, b| a.cmp(b));
+}
+
+/// Sort stably and in parallel using a comparison function.
+///
+/// The slice is cut into one chunk per thread, and the chunks are sorted
+/// with `stable_sort_by_with_buffer` in parallel. Pairs of sorted runs are
+/// then merged into a buffer and back. Each merge is split into independent
+/// pieces by binary searching for where each piece starts in both runs, so
+/// that the merges also use every thread. This allocates a buffer of the
+/// same length as the slice.
+///
+/// Slices shorter than 16384 elements are sorted sequentially; see
+/// `par_stable_sort_by_with_min_len` to tune this.
+///
+/// # Example
+///
+///     let mut v: Vec<(u32, usize)> = (0..100_000).map(|i| (i % 7, i as usize)).collect();
+///     ::quickersort::par_stable_sort_by(&mut v, &|a, b| a.0.cmp(&b.0));
+///     assert!(v.windows(2).all(|w| w[0] < w[1]));
+pub fn par_stable_sort_by<T: Send, C: Fn(&T, &T) -> Ordering + Sync>(v: &mut [T], compare: &C) {
+    par_stable_sort_by_with_min_len(v, DEFAULT_MIN_LEN, compare);
+}
+
+/// Sort stably and in parallel using a comparison function, without
+/// splitting the work into pieces shorter than `min_len`.
+///
+/// See `par_stable_sort_by` for details.
+pub fn par_stable_sort_by_with_min_len<T: Send, C: Fn(&T, &T) -> Ordering + Sync>(v: &mut [T], min_len: usize,
+                                                                                  compare: &C) {
+    par_stable_sort_by_with_threads(v, min_len, available_threads(), compare);
+}
+
+/// Like `par_stable_sort_by_with_min_len`, but with up to `threads` threads
+/// instead of the number the machine has, so that the tests run the parallel
+/// code on any machine.
+#[doc(hidden)]
+pub fn par_stable_sort_by_with_threads<T: Send, C: Fn(&T, &T) -> Ordering + Sync>(v: &mut [T], min_len: usize,
+                                                                                  threads: usize, compare: &C) {
+    let n = v.len();
+    if size_of::<T>() == 0 || n <= min_len || threads <= 1 {
+        stable_sort_by(v, compare);
+        return;
+    }
+    // `usize::div_ceil` would need a newer Rust than `thread::scope` does.
+    #[allow(clippy::manual_div_ceil)]
+    let chunk_len = max(max(min_len, 1), (n + threads - 1) / threads);
+
+    let mut buf: Vec<MaybeUninit<T>> = Vec::with_capacity(n);
+    unsafe {
+        buf.set_len(n);
+    }
+
+    let idle = AtomicUsize::new(threads - 1);
+    thread::scope(|scope| {
+        for (chunk, scratch) in v.chunks_mut(chunk_len).zip(buf.chunks_mut(chunk_len)) {
+            scope.spawn(move || stable_sort_by_with_buffer(chunk, scratch, compare));
+        }
+    });
+
+    // Merge runs back and forth between `v` and `buf`. Merging only reads
+    // from the source, so if `compare` unwinds, the source still holds every
+    // element exactly once; `CopyOnDrop` moves them back into `v` if needed.
+    let v_ptr = SendPtr(v.as_mut_ptr());
+    let buf_ptr = SendPtr(buf.as_mut_ptr() as *mut T);
+    let mut run_len = chunk_len;
+    let mut in_buf = false;
+    while run_len < n {
+        unsafe {
+            if in_buf {
+                let guard = CopyOnDrop { src: buf_ptr.0, dest: v_ptr.0, len: n };
+                par_merge_runs(buf_ptr, v_ptr, n, run_len, chunk_len, compare, &idle);
+                mem::forget(guard);
+            } else {
+                par_merge_runs(v_ptr, buf_ptr, n, run_len, chunk_len, compare, &idle);
+            }
+        }
+        in_buf = !in_buf;
+        run_len *= 2;
+    }
+    if in_buf {
+        unsafe {
+            ptr::copy_nonoverlapping(buf_ptr.0, v_ptr.0, n);
+        }
+    }
+}
+
+/// Sort stably and in parallel using a conversion function.
+///
+/// See `par_stable_sort_by` for details.
+pub fn par_stable_sort_by_key<T: Send, K: Ord, F: Fn(&T) -> K + Sync>(v: &mut [T], key: F) {
+    par_stable_sort_by(v, &|a, b| key(a).cmp(&key(b)));
+}
+
+/// Sort stably and in parallel using the default comparison function.
+///
+/// See `par_stable_sort_by` for details.
+pub fn par_stable_sort<T: Ord + Send>(v: &mut [T]) {
+    par_stable_sort_by(v, &|a, b| a.cmp(b));
+}
+
+/// The number of threads the sorts use by default.
+fn available_threads() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+/// Like `do_introsort`, but every partition except the last one is passed to
+/// `fork`. Each partition keeps counting towards the same `heapsort_depth`,
+/// so the O(n log(n)) bound holds on every thread.
+fn par_introsort<'scope, 'env, T: Send, C: Fn(&T, &T) -> Ordering + Sync>(scope: &'scope Scope<'scope, 'env>,
+                                                                          v: &'scope mut [T], compare: &'scope C,
+                                                                          rec: u32, heapsort_depth: u32,
+                                                                          min_len: usize, idle: &'scope AtomicUsize) {
+    // The sequential parts take the comparison function by `&mut`.
+    let mut seq_compare = |a: &T, b: &T| compare(a, b);
+    if v.len() <= min_len {
+        introsort(v, &mut seq_compare, rec, heapsort_depth, None);
+        return;
+    }
+    // Partitioning needs more elements than `min_len` may leave.
+    if maybe_insertion_sort(v, &mut seq_compare) {
+        return;
+    }
+
+    if rec > heapsort_depth {
+        heapsort_mut(v, &mut seq_compare);
+        return;
+    }
+
+    let (e2, e3, e4, swapped) = choose_pivots(v, &mut seq_compare);
+
+    if !swapped && capped_insertion_sort(v, &mut seq_compare) {
+        return;
+    }
+
+    if compare(&v[e2], &v[e4]) != Equal {
+        let (left_pivot, right_pivot) = DualPivotSort::partition(v, (e2, e4), &mut seq_compare, None);
+        let (left, rest) = v.split_at_mut(left_pivot);
+        let (middle, rest) = rest[1..].split_at_mut(right_pivot - left_pivot - 1);
+        let right = &mut rest[1..];
+        fork(scope, left, compare, rec + 1, heapsort_depth, min_len, idle);
+        fork(scope, middle, compare, rec + 1, heapsort_depth, min_len, idle);
+        par_introsort(scope, right, compare, rec + 1, heapsort_depth, min_len, idle);
+    } else {
+        let n = v.len();
+        let (l, r) = fat_partition(v, e3, &mut seq_compare);
+        let (left, rest) = v.split_at_mut(l);
+        let right = &mut rest[n - l - r..];
+        fork(scope, left, compare, rec + 1, heapsort_depth, min_len, idle);
+        par_introsort(scope, right, compare, rec + 1, heapsort_depth, min_len, idle);
+    }
+}
+
+/// Sort `v` on a new thread if fewer than the maximum number of threads are
+/// busy, and on the current thread otherwise.
+fn fork<'scope, 'env, T: Send, C: Fn(&T, &T) -> Ordering + Sync>(scope: &'scope Scope<'scope, 'env>,
+                                                                 v: &'scope mut [T], compare: &'scope C,
+                                                                 rec: u32, heapsort_depth: u32,
+                                                                 min_len: usize, idle: &'scope AtomicUsize) {
+    if v.len() > min_len && try_acquire(idle) {
+        scope.spawn(move || {
+            par_introsort(scope, v, compare, rec, heapsort_depth, min_len, idle);
+            idle.fetch_add(1, Release);
+        });
+    } else {
+        par_introsort(scope, v, compare, rec, heapsort_depth, min_len, idle);
+    }
+}
+
+/// Take one thread from the count of idle threads, if there are any.
+fn try_acquire(idle: &AtomicUsize) -> bool {
+    let mut n = idle.load(Relaxed);
+    while n > 0 {
+        match idle.compare_exchange_weak(n, n - 1, Acquire, Relaxed) {
+            Ok(_) => return true,
+            Err(current) => n = current,
+        }
+    }
+    false
+}
+
+/// A pointer that may be sent to the threads of `par_stable_sort_by`, which
+/// only ever access disjoint parts of the memory behind it at the same time.
+struct SendPtr<T>(*mut T);
+
+impl<T> Clone for SendPtr<T> {
+    fn clone(&self) -> SendPtr<T> {
+        *self
+    }
+}
+
+impl<T> Copy for SendPtr<T> {}
+
+unsafe impl<T: Send> Send for SendPtr<T> {}
+unsafe impl<T: Send> Sync for SendPtr<T> {}
+
+/// Moves `len` elements from `src` to `dest` when dropped.
+struct CopyOnDrop<T> {
+    src: *const T,
+    dest: *mut T,
+    len: usize,
+}
+
+impl<T> Drop for CopyOnDrop<T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::copy_nonoverlapping(self.src, self.dest, self.len);
+        }
+    }
+}
+
+/// Merge each pair of adjacent sorted runs of length `run_len` in `src` into
+/// `dest`, in pieces of about `piece_len` elements. A piece is merged on a
+/// new thread if one of the threads counted by `idle` is free, and on the
+/// current thread otherwise.
+///
+/// `src` and `dest` must both be valid for `n` elements, and must not
+/// overlap.
+unsafe fn par_merge_runs<T: Send, C: Fn(&T, &T) -> Ordering + Sync>(src: SendPtr<T>, dest: SendPtr<T>, n: usize,
+                                                                      run_len: usize, piece_len: usize,
+                                                                      compare: &C, idle: &AtomicUsize) {
+    // Find every split point before starting any thread, so that no element
+    // is ever looked at by two threads at the same time.
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    while start < n {
+        let mid = min(start + run_len, n);
+        let end = min(start + 2 * run_len, n);
+        let a = slice::from_raw_parts(src.0.add(start), mid - start);
+        let b = slice::from_raw_parts(src.0.add(mid), end - mid);
+        let mut k = 0;
+        let mut i = 0;
+        while k < end - start {
+            let next_k = min(k + piece_len, end - start);
+            let next_i = co_rank(next_k, a, b, compare);
+            pieces.push((start + i, start + next_i, mid + k - i, mid + next_k - next_i, start + k));
+            k = next_k;
+            i = next_i;
+        }
+        start = end;
+    }
+
+    thread::scope(|scope| {
+        for &(a_start, a_end, b_start, b_end, out) in &pieces {
+            let merge = move || {
+                let a = slice::from_raw_parts(src.0.add(a_start), a_end - a_start);
+                let b = slice::from_raw_parts(src.0.add(b_start), b_end - b_start);
+                merge_into(a, b, dest.0.add(out), compare);
+            };
+            if try_acquire(idle) {
+                scope.spawn(move || {
+                    merge();
+                    idle.fetch_add(1, Release);
+                });
+            } else {
+                merge();
+            }
+        }
+    });
+}
+
+/// Returns how many elements of `a` are among the first `k` elements of the
+/// stable merge of `a` and `b`.
+fn co_rank<T, C: Fn(&T, &T) -> Ordering>(k: usize, a: &[T], b: &[T], compare: &C) -> usize {
+    let mut lo = k.saturating_sub(b.len());
+    let mut hi = min(k, a.len());
+    while lo < hi {
+        let i = lo + (hi - lo) / 2;
+        let j = k - i;
+        // Equal elements are taken from `a` first, so `a[i]` belongs before
+        // `b[j - 1]` unless it is strictly greater.
+        if j > 0 && compare(&b[j - 1], &a[i]) != Less {
+            lo = i + 1;
+        } else {
+            hi = i;
+        }
+    }
+    lo
+}
+
+/// Stably merge `a` and `b` into `dest`, by copying.
+///
+/// `dest` must be valid for writes of `a.len() + b.len()` elements, and must
+/// not overlap `a` or `b`.
+unsafe fn merge_into<T, C: Fn(&T, &T) -> Ordering>(a: &[T], b: &[T], dest: *mut T, compare: &C) {
+    let mut i = 0;
+    let mut j = 0;
+    let mut out = dest;
+    while i < a.len() && j < b.len() {
+        if compare(&b[j], &a[i]) == Less {
+            ptr::copy_nonoverlapping(&b[j], out, 1);
+            j += 1;
+        } else {
+            ptr::copy_nonoverlapping(&a[i], out, 1);
+            i += 1;
+        }
+        out = out.add(1);
+    }
+    ptr::copy_nonoverlapping(a.as_ptr().add(i), out, a.len() - i);
+    out = out.add(a.len() - i);
+    ptr::copy_nonoverlapping(b.as_ptr().add(j), out, b.len() - j);
+}
@@ -0,0 +1,103 @@
+// This file is licensed under the same terms as Rust itself.
+
+use core::cmp::Ordering::*;
+use core::fmt;
+use super::sort::sort_by;
+use super::float::{move_to_end, move_to_front};
+
+/// Where `sort_partial_by` puts the elements that are incomparable with
+/// themselves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IncomparablePolicy {
+    /// Move them to the front of the slice.
+    First,
+    /// Move them to the back of the slice, like `sort_floats` does with NaNs.
+    Last,
+    /// Do not sort, and return an `IncomparableError` if there are any.
+    Error,
+}
+
+/// The error returned by `sort_partial_by` when there is an incomparable
+/// element and the policy is `IncomparablePolicy::Error`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IncomparableError {
+    position: usize,
+}
+
+impl IncomparableError {
+    /// Returns the position of the first incomparable element.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl fmt::Display for IncomparableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "element at position {} is incomparable", self.position)
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for IncomparableError {}
+
+/// Sorts a slice of partially ordered elements, after moving the elements
+/// that are incomparable with themselves out of the way.
+///
+/// This is `sort_partial_by` with the elements for which
+/// `x.partial_cmp(x)` is `None` as the incomparable ones.
+///
+/// # Example
+///
+///     use quickersort::IncomparablePolicy;
+///     use std::f64::NAN;
+///
+///     let mut v = [Some(2.0), None, Some(NAN), Some(-1.0)];
+///     assert_eq!(quickersort::sort_partial(&mut v, IncomparablePolicy::First), Ok(1));
+///     assert!(v[0].unwrap().is_nan());
+///     assert_eq!(v[1..], [None, Some(-1.0), Some(2.0)]);
+pub fn sort_partial<T: PartialOrd>(v: &mut [T], policy: IncomparablePolicy) -> Result<usize, IncomparableError> {
+    sort_partial_by(v, |x| x.partial_cmp(x).is_none(), policy)
+}
+
+/// Sorts a slice of partially ordered elements, after moving the elements
+/// for which `is_incomparable` is true out of the way.
+///
+/// This is the strategy of `sort_floats` for any `PartialOrd` type: the
+/// incomparable elements, like NaNs, are moved to the front or back of `v`
+/// as `policy` says, in an unspecified order, and the rest are sorted with
+/// `partial_cmp`. The number of incomparable elements is returned. With
+/// `IncomparablePolicy::Error`, `v` is left as it is if there are any.
+///
+/// If some of the rest are still incomparable with each other, such as
+/// overlapping intervals, they are treated as equal. The slice is then
+/// still reordered safely, but may not be sorted.
+///
+/// # Example
+///
+///     use quickersort::IncomparablePolicy;
+///
+///     #[derive(Debug, PartialEq, PartialOrd)]
+///     struct Reading(Option<u32>);
+///
+///     let mut v = [Reading(Some(3)), Reading(None), Reading(Some(1))];
+///     let unknown = |r: &Reading| r.0.is_none();
+///     assert!(quickersort::sort_partial_by(&mut v, unknown, IncomparablePolicy::Error).is_err());
+///     assert_eq!(quickersort::sort_partial_by(&mut v, unknown, IncomparablePolicy::Last), Ok(1));
+///     assert_eq!(v, [Reading(Some(1)), Reading(Some(3)), Reading(None)]);
+pub fn sort_partial_by<T, P>(v: &mut [T], is_incomparable: P, policy: IncomparablePolicy)
+                             -> Result<usize, IncomparableError>
+    where T: PartialOrd, P: Fn(&T) -> bool
+{
+    let (start, end) = match policy {
+        IncomparablePolicy::First => (move_to_front(v, &is_incomparable), v.len()),
+        IncomparablePolicy::Last => (0, move_to_end(v, &is_incomparable)),
+        IncomparablePolicy::Error => {
+            if let Some(position) = v.iter().position(&is_incomparable) {
+                return Err(IncomparableError { position });
+            }
+            (0, v.len())
+        }
+    };
+    sort_by(&mut v[start..end], &|x: &T, y: &T| x.partial_cmp(y).unwrap_or(Equal));
+    Ok(v.len() - (end - start))
+}
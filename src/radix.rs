@@ -0,0 +1,237 @@
+// This file is licensed under the same terms as Rust itself.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use super::sort::{sort_by, apply_permutation};
+#[cfg(feature = "alloc")]
+use super::merge::stable_sort_by;
+
+/// Slices shorter than this many elements per key byte are sorted with
+/// `sort` instead, which is faster than moving every element once per byte.
+#[cfg(feature = "alloc")]
+pub(crate) const RADIX_SORT_THRESHOLD: usize = 256;
+
+/// Types that can be sorted by their bytes.
+///
+/// The key is `BYTES` bytes long, with byte `0` being the most significant.
+/// Comparing the keys byte by byte must give the same order as `Ord`. This is
+/// implemented for the primitive integers, `bool`, `char`, and for tuples and
+/// arrays of keys, which compare lexicographically.
+///
+/// # Example
+///
+/// Implementing `RadixKey` for a type ordered by its fields:
+///
+///     use quickersort::RadixKey;
+///
+///     #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+///     struct Version { major: u16, minor: u16 }
+///
+///     impl RadixKey for Version {
+///         const BYTES: usize = 4;
+///
+///         fn key_byte(&self, i: usize) -> u8 {
+///             (self.major, self.minor).key_byte(i)
+///         }
+///     }
+pub trait RadixKey: Copy + Ord {
+    /// The length of the key in bytes.
+    const BYTES: usize;
+
+    /// Returns byte `i` of the key, counting from the most significant byte.
+    ///
+    /// `i` is always less than `BYTES`.
+    fn key_byte(&self, i: usize) -> u8;
+}
+
+macro_rules! radix_key_int {
+    ($($t:ty, $u:ty, $flip:expr;)*) => {$(
+        impl RadixKey for $t {
+            const BYTES: usize = ::core::mem::size_of::<$t>();
+
+            #[inline]
+            fn key_byte(&self, i: usize) -> u8 {
+                // Flipping the sign bit of signed integers makes the negative
+                // numbers sort below the positive ones.
+                (((*self as $u) ^ $flip) >> (8 * (Self::BYTES - 1 - i))) as u8
+            }
+        }
+    )*}
+}
+
+radix_key_int! {
+    u8, u8, 0;
+    u16, u16, 0;
+    u32, u32, 0;
+    u64, u64, 0;
+    u128, u128, 0;
+    usize, usize, 0;
+    i8, u8, 1 << 7;
+    i16, u16, 1 << 15;
+    i32, u32, 1 << 31;
+    i64, u64, 1 << 63;
+    i128, u128, 1 << 127;
+    isize, usize, 1 << (::core::mem::size_of::<usize>() * 8 - 1);
+}
+
+impl RadixKey for bool {
+    const BYTES: usize = 1;
+
+    #[inline]
+    fn key_byte(&self, _: usize) -> u8 {
+        *self as u8
+    }
+}
+
+impl RadixKey for char {
+    // The largest `char` is `0x10FFFF`, which fits in three bytes.
+    const BYTES: usize = 3;
+
+    #[inline]
+    fn key_byte(&self, i: usize) -> u8 {
+        (*self as u32 >> (8 * (2 - i))) as u8
+    }
+}
+
+macro_rules! radix_key_tuple {
+    ($(($($name:ident . $idx:tt),+))*) => {$(
+        impl<$($name: RadixKey),+> RadixKey for ($($name,)+) {
+            const BYTES: usize = 0 $(+ $name::BYTES)+;
+
+            #[inline]
+            #[allow(unused_assignments)]
+            fn key_byte(&self, i: usize) -> u8 {
+                let mut start = 0;
+                $(
+                    if i < start + $name::BYTES {
+                        return self.$idx.key_byte(i - start);
+                    }
+                    start += $name::BYTES;
+                )+
+                panic!("key byte index out of range");
+            }
+        }
+    )*}
+}
+
+radix_key_tuple! {
+    (A.0)
+    (A.0, B.1)
+    (A.0, B.1, C.2)
+    (A.0, B.1, C.2, D.3)
+    (A.0, B.1, C.2, D.3, E.4)
+    (A.0, B.1, C.2, D.3, E.4, F.5)
+}
+
+impl<T: RadixKey, const N: usize> RadixKey for [T; N] {
+    const BYTES: usize = T::BYTES * N;
+
+    #[inline]
+    fn key_byte(&self, i: usize) -> u8 {
+        self[i / T::BYTES].key_byte(i % T::BYTES)
+    }
+}
+
+/// Sort integers, or any other `RadixKey`, using a least significant digit
+/// radix sort.
+///
+/// This takes one pass over the slice to count every byte of every element,
+/// and then one pass per byte to move the elements into a buffer of the same
+/// length and back. Bytes that are the same in every element are skipped, so
+/// small numbers in a wide type cost no more than in a narrow one. Slices
+/// shorter than 256 elements per byte of the type (1024 for `u32`) are
+/// sorted with `sort` instead.
+///
+/// # Example
+///
+///     let mut v = [5i64, -3, 1 << 40, 0, -(1 << 40), 2];
+///     ::quickersort::radix_sort(&mut v);
+///     assert_eq!(v, [-(1 << 40), -3, 0, 2, 5, 1 << 40]);
+#[cfg(feature = "alloc")]
+pub fn radix_sort<T: RadixKey>(v: &mut [T]) {
+    if v.len() < RADIX_SORT_THRESHOLD * T::BYTES {
+        sort_by(v, &|a, b| a.cmp(b));
+        return;
+    }
+    lsd_radix_sort(v, &|x: &T| *x);
+}
+
+/// Sort stably by a key using a least significant digit radix sort.
+///
+/// The key of every element is computed once, and the keys are radix sorted
+/// along with the position of their element, as in `radix_sort`. The
+/// elements are then swapped into place. Slices shorter than 256 elements
+/// per byte of the key are sorted with `stable_sort_by` instead.
+///
+/// Elements with equal keys keep their relative order, so sorting by a
+/// secondary key first and by the primary key afterwards sorts by both.
+///
+/// # Example
+///
+///     struct Event { ts: u64, kind: u8 }
+///
+///     let mut v = vec![Event { ts: 3, kind: 1 }, Event { ts: 1, kind: 2 }, Event { ts: 2, kind: 1 }];
+///     ::quickersort::radix_sort_by_key(&mut v, |e| e.ts);
+///     ::quickersort::radix_sort_by_key(&mut v, |e| e.kind);
+///     let order: Vec<_> = v.iter().map(|e| (e.kind, e.ts)).collect();
+///     assert_eq!(order, [(1, 2), (1, 3), (2, 1)]);
+#[cfg(feature = "alloc")]
+pub fn radix_sort_by_key<T, K: RadixKey, F: Fn(&T) -> K>(v: &mut [T], key: F) {
+    if v.len() < RADIX_SORT_THRESHOLD * K::BYTES {
+        stable_sort_by(v, &|a, b| key(a).cmp(&key(b)));
+        return;
+    }
+
+    let mut keys: Vec<(K, usize)> = v.iter().enumerate().map(|(i, x)| (key(x), i)).collect();
+    lsd_radix_sort(&mut keys, &|x: &(K, usize)| x.0);
+
+    apply_permutation(v, &mut keys, |k| &mut k.1);
+}
+
+/// Sort `v` by the keys given by `key`, starting at the least significant
+/// byte.
+///
+/// `key` is called once per element to count the bytes of every key, and
+/// once per element for each byte that is not the same in every key. This
+/// is stable.
+#[cfg(feature = "alloc")]
+pub(crate) fn lsd_radix_sort<T: Copy, K: RadixKey, F: Fn(&T) -> K>(v: &mut [T], key: &F) {
+    let n = v.len();
+    let bytes = K::BYTES;
+    let mut counts = Vec::new();
+    counts.resize(bytes, [0usize; 256]);
+    for x in v.iter() {
+        let k = key(x);
+        for (i, count) in counts.iter_mut().enumerate() {
+            count[k.key_byte(i) as usize] += 1;
+        }
+    }
+
+    let mut buf = v.to_vec();
+    let mut in_buf = false;
+    for i in (0..bytes).rev() {
+        let count = &mut counts[i];
+        if count.contains(&n) {
+            continue;
+        }
+        // Turn the counts into the position of the first element of each
+        // bucket.
+        let mut pos = 0;
+        for c in count.iter_mut() {
+            let len = *c;
+            *c = pos;
+            pos += len;
+        }
+        let (src, dest) = if in_buf { (&buf[..], &mut v[..]) } else { (&v[..], &mut buf[..]) };
+        for x in src {
+            let bucket = &mut count[key(x).key_byte(i) as usize];
+            dest[*bucket] = *x;
+            *bucket += 1;
+        }
+        in_buf = !in_buf;
+    }
+    if in_buf {
+        v.copy_from_slice(&buf);
+    }
+}
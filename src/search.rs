@@ -0,0 +1,214 @@
+// This file is licensed under the same terms as Rust itself.
+
+use core::ops::{Bound, Range, RangeBounds};
+use super::float::{Float, find_first_zero};
+use super::total::Total;
+
+/// Returns the position of the first element of `v` that is not less than
+/// `x`, in the order used by `sort_floats`.
+///
+/// `v` must be sorted by `sort_floats`. `-0` is less than `+0`, and all NaNs
+/// are equal and greater than any number, so searching for a NaN finds the
+/// first NaN. If there is no such element, this returns `v.len()`.
+///
+/// # Example
+///
+///     let v = [-1.0, -0.0, 0.0, 0.0, 2.0, ::std::f64::NAN];
+///     assert_eq!(::quickersort::float_lower_bound(&v, 0.0), 2);
+///     assert_eq!(::quickersort::float_lower_bound(&v, -0.0), 1);
+///     assert_eq!(::quickersort::float_lower_bound(&v, ::std::f64::NAN), 5);
+pub fn float_lower_bound<T: Float>(v: &[T], x: T) -> usize {
+    v.partition_point(|&y| Total(y) < Total(x))
+}
+
+/// Returns the position after the last element of `v` that is not greater
+/// than `x`, in the order used by `sort_floats`.
+///
+/// `v` must be sorted by `sort_floats`. See `float_lower_bound`.
+pub fn float_upper_bound<T: Float>(v: &[T], x: T) -> usize {
+    v.partition_point(|&y| Total(y) <= Total(x))
+}
+
+/// Returns the positions of the elements of `v` that are equal to `x`, in
+/// the order used by `sort_floats`.
+///
+/// `v` must be sorted by `sort_floats`. `-0` and `+0` are not equal, and
+/// all NaNs are. Use `float_range(v, -0.0..=0.0)` to find both zeros.
+///
+/// # Example
+///
+///     use std::f64::NAN;
+///
+///     let v = [-1.0, -0.0, 0.0, 0.0, 2.0, NAN, -NAN];
+///     assert_eq!(::quickersort::float_equal_range(&v, 0.0), 2..4);
+///     assert_eq!(::quickersort::float_equal_range(&v, 1.0), 4..4);
+///     assert_eq!(::quickersort::float_equal_range(&v, NAN), 5..7);
+pub fn float_equal_range<T: Float>(v: &[T], x: T) -> Range<usize> {
+    let start = float_lower_bound(v, x);
+    start..start + float_upper_bound(&v[start..], x)
+}
+
+/// Returns the positions of the elements of `v` that are in `range`, in the
+/// order used by `sort_floats`.
+///
+/// `v` must be sorted by `sort_floats`. A NaN bound is greater than any
+/// number, so `x..NAN` is every number from `x` on. If the range is empty,
+/// this returns an empty range.
+///
+/// # Example
+///
+///     use std::f64::{INFINITY, NAN};
+///
+///     let v = [-INFINITY, -1.0, -0.0, 0.0, 0.5, 2.0, INFINITY, NAN];
+///     assert_eq!(::quickersort::float_range(&v, -1.0..0.5), 1..4);
+///     assert_eq!(::quickersort::float_range(&v, -0.0..=0.0), 2..4);
+///     assert_eq!(::quickersort::float_range(&v, 0.0..NAN), 3..7);
+///     assert_eq!(::quickersort::float_range(&v, ..), 0..8);
+pub fn float_range<T: Float, R: RangeBounds<T>>(v: &[T], range: R) -> Range<usize> {
+    bounds(v.len(), range, |x| float_lower_bound(v, x), |x| float_upper_bound(v, x))
+}
+
+/// A slice sorted by `sort_floats`, with the boundaries between its kinds of
+/// numbers.
+///
+/// Creating the view takes a few binary searches. Afterwards, the regions
+/// of `-inf`, negative numbers, `-0`, `+0`, positive numbers, `+inf` and
+/// NaNs are known, and searches only look in the region of the number
+/// searched for. The searches give the same results as `float_lower_bound`
+/// and the other functions.
+///
+/// # Example
+///
+///     use quickersort::SortedFloatsView;
+///     use std::f64::{INFINITY, NAN};
+///
+///     let v = [-1.0, -0.0, 0.0, 0.0, 2.0, 3.0, INFINITY, NAN];
+///     let view = SortedFloatsView::new(&v);
+///     assert_eq!(view.negatives(), 0..1);
+///     assert_eq!(view.zeros(), 1..4);
+///     assert_eq!(view.positives(), 4..7);
+///     assert_eq!(view.infinities(), (0..0, 6..7));
+///     assert_eq!(view.nans(), 7..8);
+///     assert_eq!(view.equal_range(0.0), 2..4);
+///     assert_eq!(view.range(1.0..), 4..8);
+#[derive(Clone, Copy, Debug)]
+pub struct SortedFloatsView<'a, T: 'a> {
+    v: &'a [T],
+    // The start of the negative numbers, `-0`, `+0`, the positive numbers,
+    // `+inf` and the NaNs, in that order. `-inf` starts at 0.
+    starts: [usize; 6],
+}
+
+impl<'a, T: Float> SortedFloatsView<'a, T> {
+    /// Creates a view of `v`, which must be sorted by `sort_floats`.
+    pub fn new(v: &'a [T]) -> SortedFloatsView<'a, T> {
+        let nans = v.partition_point(|x| !x.is_nan());
+        let numbers = &v[..nans];
+        let zeros = find_first_zero(numbers);
+        let negatives = numbers[..zeros].partition_point(|x| x.is_infinite());
+        let pos_zeros = zeros + numbers[zeros..].partition_point(|&x| x == T::zero() && x.is_sign_negative());
+        let positives = pos_zeros + numbers[pos_zeros..].partition_point(|&x| x == T::zero());
+        let infinities = positives + numbers[positives..].partition_point(|x| !x.is_infinite());
+        SortedFloatsView { v, starts: [negatives, zeros, pos_zeros, positives, infinities, nans] }
+    }
+
+    /// Returns the slice.
+    pub fn as_slice(&self) -> &'a [T] {
+        self.v
+    }
+
+    /// Returns the positions of the negative numbers, `-inf` included.
+    pub fn negatives(&self) -> Range<usize> {
+        0..self.starts[1]
+    }
+
+    /// Returns the positions of the zeros, `-0` and `+0`.
+    pub fn zeros(&self) -> Range<usize> {
+        self.starts[1]..self.starts[3]
+    }
+
+    /// Returns the positions of the positive numbers, `+inf` included.
+    pub fn positives(&self) -> Range<usize> {
+        self.starts[3]..self.starts[5]
+    }
+
+    /// Returns the positions of the infinities, `-inf` and `+inf`.
+    ///
+    /// These are not next to each other, so the first range is `-inf` and
+    /// the second `+inf`.
+    pub fn infinities(&self) -> (Range<usize>, Range<usize>) {
+        (0..self.starts[0], self.starts[4]..self.starts[5])
+    }
+
+    /// Returns the positions of the NaNs.
+    pub fn nans(&self) -> Range<usize> {
+        self.starts[5]..self.v.len()
+    }
+
+    /// Returns the positions of the elements equal to `x`, and whether `x`
+    /// is the only value there can be in them.
+    fn region(&self, x: T) -> (Range<usize>, bool) {
+        let s = &self.starts;
+        if x.is_nan() {
+            (s[5]..self.v.len(), true)
+        } else if x.is_infinite() {
+            if x.is_sign_negative() { (0..s[0], true) } else { (s[4]..s[5], true) }
+        } else if x == T::zero() {
+            if x.is_sign_negative() { (s[1]..s[2], true) } else { (s[2]..s[3], true) }
+        } else if x < T::zero() {
+            (s[0]..s[1], false)
+        } else {
+            (s[3]..s[4], false)
+        }
+    }
+
+    /// Returns the same as `float_lower_bound`.
+    pub fn lower_bound(&self, x: T) -> usize {
+        match self.region(x) {
+            (region, true) => region.start,
+            (region, false) => region.start + float_lower_bound(&self.v[region], x),
+        }
+    }
+
+    /// Returns the same as `float_upper_bound`.
+    pub fn upper_bound(&self, x: T) -> usize {
+        match self.region(x) {
+            (region, true) => region.end,
+            (region, false) => region.start + float_upper_bound(&self.v[region], x),
+        }
+    }
+
+    /// Returns the same as `float_equal_range`.
+    pub fn equal_range(&self, x: T) -> Range<usize> {
+        match self.region(x) {
+            (region, true) => region,
+            (region, false) => {
+                let start = region.start;
+                let range = float_equal_range(&self.v[region], x);
+                start + range.start..start + range.end
+            }
+        }
+    }
+
+    /// Returns the same as `float_range`.
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Range<usize> {
+        bounds(self.v.len(), range, |x| self.lower_bound(x), |x| self.upper_bound(x))
+    }
+}
+
+/// Turn `range` into positions in a slice of length `len`.
+fn bounds<T: Float, R, L, U>(len: usize, range: R, lower_bound: L, upper_bound: U) -> Range<usize>
+    where R: RangeBounds<T>, L: Fn(T) -> usize, U: Fn(T) -> usize
+{
+    let start = match range.start_bound() {
+        Bound::Included(&x) => lower_bound(x),
+        Bound::Excluded(&x) => upper_bound(x),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&x) => upper_bound(x),
+        Bound::Excluded(&x) => lower_bound(x),
+        Bound::Unbounded => len,
+    };
+    if end < start { start..start } else { start..end }
+}
@@ -4,8 +4,10 @@
 
 use core::cmp::Ordering;
 use core::cmp::Ordering::*;
+use core::cell::Cell;
 use core::cmp::{min, max};
 use core::mem::{size_of, swap};
+use core::ops::Range;
 use core::ptr;
 use nodrop::NoDrop;
 
@@ -33,9 +35,29 @@ const INSERTION_SORTED_CAP: usize = 8;
 ///     ::quickersort::sort_by(&mut unsorted, &|a, b| b.cmp(a));
 ///     assert_eq!(unsorted, sorted);
 pub fn sort_by<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C) {
+    sort_by_mut(v, &mut |a, b| compare(a, b));
+}
+
+/// Sort using a comparison function that may mutate its state, such as one
+/// that counts or caches its comparisons.
+///
+/// This is the same sort as `sort_by`, which takes a `Fn` so that it can be
+/// shared, and is otherwise the same.
+///
+/// # Example
+///
+///     let mut v = [5, 1, 4, 2, 3];
+///     let mut comparisons = 0;
+///     ::quickersort::sort_by_mut(&mut v, &mut |a, b| {
+///         comparisons += 1;
+///         a.cmp(b)
+///     });
+///     assert_eq!(v, [1, 2, 3, 4, 5]);
+///     assert!(comparisons >= 4);
+pub fn sort_by_mut<T, C: FnMut(&T, &T) -> Ordering>(v: &mut [T], compare: &mut C) {
     if maybe_insertion_sort(v, compare) { return; }
     let heapsort_depth = (3 * log2(v.len())) / 2;
-    do_introsort(v, compare, 0, heapsort_depth);
+    do_introsort(v, compare, 0, heapsort_depth, None);
 }
 
 /// Sort using a conversion function.
@@ -66,17 +88,402 @@ pub fn sort_by_key<T, K: Ord, F: Fn(&T) -> K>(v: &mut [T], key: F) {
     sort_by(v, &|a, b| key(a).cmp(&key(b)));
 }
 
+/// Sort using a conversion function that may mutate its state.
+///
+/// See `sort_by_mut`.
+pub fn sort_by_key_mut<T, K: Ord, F: FnMut(&T) -> K>(v: &mut [T], mut key: F) {
+    sort_by_mut(v, &mut |a, b| key(a).cmp(&key(b)));
+}
+
+/// Sort using a comparison function that can fail.
+///
+/// If `compare` returns an error, it is not called again, the sort stops
+/// once the partitioning step it is in is done, and the error is returned.
+/// `v` is then a permutation of the input in an unspecified order, just as
+/// when `compare` panics.
+///
+/// # Example
+///
+///     let mut v = ["3", "1", "2"];
+///     assert_eq!(::quickersort::try_sort_by(&mut v, &mut |a, b| {
+///         Ok::<_, ::std::num::ParseIntError>(a.parse::<u32>()?.cmp(&b.parse()?))
+///     }), Ok(()));
+///     assert_eq!(v, ["1", "2", "3"]);
+///
+///     let mut v = ["3", "one", "2"];
+///     assert!(::quickersort::try_sort_by(&mut v, &mut |a, b| {
+///         Ok::<_, ::std::num::ParseIntError>(a.parse::<u32>()?.cmp(&b.parse()?))
+///     }).is_err());
+pub fn try_sort_by<T, E, C>(v: &mut [T], compare: &mut C) -> Result<(), E>
+    where C: FnMut(&T, &T) -> Result<Ordering, E>
+{
+    let failed = Cell::new(false);
+    let mut error = None;
+    let heapsort_depth = (3 * log2(v.len())) / 2;
+    // The rest of the current step still compares, so answer for `compare`.
+    introsort(v, &mut |a, b| {
+        if failed.get() {
+            return Equal;
+        }
+        match compare(a, b) {
+            Ok(ordering) => ordering,
+            Err(e) => {
+                error = Some(e);
+                failed.set(true);
+                Equal
+            }
+        }
+    }, 0, heapsort_depth, Some(&failed));
+    match error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Sort using a conversion function that can fail.
+///
+/// See `try_sort_by`.
+///
+/// # Example
+///
+///     let mut v = ["10", "9", "x"];
+///     assert!(::quickersort::try_sort_by_key(&mut v, |s| s.parse::<u32>()).is_err());
+///     v.sort();
+///     assert_eq!(v, ["10", "9", "x"]);
+pub fn try_sort_by_key<T, E, K: Ord, F: FnMut(&T) -> Result<K, E>>(v: &mut [T], mut key: F) -> Result<(), E> {
+    try_sort_by(v, &mut |a, b| Ok(key(a)?.cmp(&key(b)?)))
+}
+
 /// Sort using the default comparison function.
 pub fn sort<T: Ord>(v: &mut [T]) {
     sort_by(v, &|a, b| a.cmp(b));
 }
 
-fn introsort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C, rec: u32, heapsort_depth: u32) {
-    if maybe_insertion_sort(v, compare) { return; }
-    do_introsort(v, compare, rec, heapsort_depth);
+/// Reorder the slice using a comparison function, such that the element at
+/// `index` is at its final sorted position.
+///
+/// Every element before `index` compares less than or equal to it, and every
+/// element after it compares greater than or equal to it. Returns the part
+/// before `index`, the element at `index` and the part after it.
+///
+/// This runs in O(n) time on average and O(n log(n)) in the worst case.
+///
+/// # Panics
+///
+/// Panics if `index >= v.len()`.
+///
+/// # Example
+///
+///     let mut v = [5, 1, 9, 3, 7, 2, 8];
+///     {
+///         let (smaller, median, larger) =
+///             ::quickersort::select_nth_by(&mut v, 3, &|a, b| a.cmp(b));
+///         assert_eq!(*median, 5);
+///         assert!(smaller.iter().all(|x| *x <= 5));
+///         assert!(larger.iter().all(|x| *x >= 5));
+///     }
+///     assert_eq!(v[3], 5);
+pub fn select_nth_by<'a, T, C: Fn(&T, &T) -> Ordering>(v: &'a mut [T], index: usize, compare: &C)
+                                                       -> (&'a mut [T], &'a mut T, &'a mut [T]) {
+    assert!(index < v.len(), "select_nth index {} out of range for slice of length {}", index, v.len());
+    let heapsort_depth = (3 * log2(v.len())) / 2;
+    introselect(v, index, &mut |a, b| compare(a, b), heapsort_depth);
+    let (left, rest) = v.split_at_mut(index);
+    let (nth, right) = rest.split_first_mut().unwrap();
+    (left, nth, right)
+}
+
+/// Reorder the slice using a conversion function, such that the element at
+/// `index` is at its final sorted position.
+///
+/// See `select_nth_by` for details.
+pub fn select_nth_by_key<T, K: Ord, F: Fn(&T) -> K>(v: &mut [T], index: usize, key: F)
+                                                    -> (&mut [T], &mut T, &mut [T]) {
+    select_nth_by(v, index, &|a, b| key(a).cmp(&key(b)))
+}
+
+/// Reorder the slice using the default comparison function, such that the
+/// element at `index` is at its final sorted position.
+///
+/// See `select_nth_by` for details.
+pub fn select_nth<T: Ord>(v: &mut [T], index: usize) -> (&mut [T], &mut T, &mut [T]) {
+    select_nth_by(v, index, &|a, b| a.cmp(b))
+}
+
+/// Sort the first `k` positions of the slice using a comparison function.
+///
+/// Afterwards `v[..k]` holds the `k` smallest elements in sorted order, and
+/// `v[k..]` holds the remaining elements in unspecified order. If `k` is at
+/// least `v.len()`, the whole slice is sorted.
+///
+/// This runs in O(n log(k)) time in the worst case.
+///
+/// # Example
+///
+///     let mut v = [5, 1, 9, 3, 7, 2, 8];
+///     ::quickersort::partial_sort_by(&mut v, 3, &|a, b| b.cmp(a));
+///     assert_eq!(v[..3], [9, 8, 7]);
+pub fn partial_sort_by<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], k: usize, compare: &C) {
+    if k >= v.len() {
+        sort_by(v, compare);
+        return;
+    }
+    if k == 0 {
+        return;
+    }
+    // Bound the partitioning work by the size of the prefix, not the slice,
+    // so that the heap fallback keeps the O(n log(k)) guarantee.
+    let heapsort_depth = (3 * log2(k)) / 2;
+    introselect(v, k - 1, &mut |a, b| compare(a, b), heapsort_depth);
+    sort_by(&mut v[..k - 1], compare);
+}
+
+/// Sort the first `k` positions of the slice using a conversion function.
+///
+/// See `partial_sort_by` for details.
+pub fn partial_sort_by_key<T, K: Ord, F: Fn(&T) -> K>(v: &mut [T], k: usize, key: F) {
+    partial_sort_by(v, k, &|a, b| key(a).cmp(&key(b)));
+}
+
+/// Sort the first `k` positions of the slice using the default comparison
+/// function.
+///
+/// See `partial_sort_by` for details.
+pub fn partial_sort<T: Ord>(v: &mut [T], k: usize) {
+    partial_sort_by(v, k, &|a, b| a.cmp(b));
+}
+
+/// Sort only the elements that belong in `range` using a comparison function.
+///
+/// Afterwards `v[range]` holds exactly the elements that a full sort would put
+/// there, in sorted order. Every element before `range.start` compares less
+/// than or equal to them, and every element from `range.end` on compares
+/// greater than or equal to them. Partitions that fall entirely outside
+/// `range` are left unsorted.
+///
+/// # Panics
+///
+/// Panics if `range.start > range.end` or `range.end > v.len()`.
+///
+/// # Example
+///
+///     let mut v = [5, 1, 9, 3, 7, 2, 8, 4, 6, 0];
+///     ::quickersort::sort_range_by(&mut v, 4..7, &|a, b| a.cmp(b));
+///     assert_eq!(v[4..7], [4, 5, 6]);
+///     assert!(v[..4].iter().all(|x| *x < 4));
+///     assert!(v[7..].iter().all(|x| *x > 6));
+pub fn sort_range_by<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], range: Range<usize>, compare: &C) {
+    assert!(range.start <= range.end, "sort_range range starts at {} but ends at {}", range.start, range.end);
+    assert!(range.end <= v.len(), "sort_range range end {} out of range for slice of length {}", range.end, v.len());
+    let heapsort_depth = (3 * log2(v.len())) / 2;
+    if range.start == range.end {
+        // Nothing to sort, but the elements still need to be split around `range.start`.
+        if range.start < v.len() {
+            introselect(v, range.start, &mut |a, b| compare(a, b), heapsort_depth);
+        }
+        return;
+    }
+    introsort_range(v, range, &mut |a, b| compare(a, b), 0, heapsort_depth);
+}
+
+/// Sort only the elements that belong in `range` using a conversion function.
+///
+/// See `sort_range_by` for details.
+pub fn sort_range_by_key<T, K: Ord, F: Fn(&T) -> K>(v: &mut [T], range: Range<usize>, key: F) {
+    sort_range_by(v, range, &|a, b| key(a).cmp(&key(b)));
+}
+
+/// Sort only the elements that belong in `range` using the default comparison
+/// function.
+///
+/// See `sort_range_by` for details.
+pub fn sort_range<T: Ord>(v: &mut [T], range: Range<usize>) {
+    sort_range_by(v, range, &|a, b| a.cmp(b));
+}
+
+/// Sort stably and in place using a comparison function.
+///
+/// Elements that compare equal keep their relative order. Unlike
+/// `stable_sort_by`, this neither allocates nor needs the "alloc" feature, and
+/// uses O(1) extra memory. It is a block merge sort based on [WikiSort], and
+/// runs in O(n log(n)) time, but it is slower than `sort_by`.
+///
+/// [WikiSort]: https://github.com/BonzaiThePenguin/WikiSort
+///
+/// # Example
+///
+///     let mut v = [(1, 'c'), (0, 'b'), (1, 'a'), (0, 'd')];
+///     ::quickersort::stable_sort_in_place_by(&mut v, &|a, b| a.0.cmp(&b.0));
+///     assert_eq!(v, [(0, 'b'), (0, 'd'), (1, 'c'), (1, 'a')]);
+pub fn stable_sort_in_place_by<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C) {
+    block_merge_sort(v, compare);
 }
 
-fn do_introsort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C, rec: u32, heapsort_depth: u32) {
+/// Sort stably and in place using a conversion function.
+///
+/// See `stable_sort_in_place_by` for details.
+pub fn stable_sort_in_place_by_key<T, K: Ord, F: Fn(&T) -> K>(v: &mut [T], key: F) {
+    stable_sort_in_place_by(v, &|a, b| key(a).cmp(&key(b)));
+}
+
+/// Sort stably and in place using the default comparison function.
+///
+/// See `stable_sort_in_place_by` for details.
+pub fn stable_sort_in_place<T: Ord>(v: &mut [T]) {
+    stable_sort_in_place_by(v, &|a, b| a.cmp(b));
+}
+
+/// Sort `v`, unless `abort` is set.
+///
+/// A sort with an `abort` flag stops before the next partitioning step once
+/// the flag is set, and skips the "assert_working_compare" checks.
+pub(crate) fn introsort<T, C: FnMut(&T, &T) -> Ordering>(v: &mut [T], compare: &mut C, rec: u32, heapsort_depth: u32,
+                                                          abort: Option<&Cell<bool>>) {
+    if aborted(abort) || maybe_insertion_sort(v, compare) { return; }
+    do_introsort(v, compare, rec, heapsort_depth, abort);
+}
+
+fn do_introsort<T, C: FnMut(&T, &T) -> Ordering>(v: &mut [T], compare: &mut C, rec: u32, heapsort_depth: u32,
+                                                 abort: Option<&Cell<bool>>) {
+    if aborted(abort) {
+        return;
+    }
+    if rec > heapsort_depth {
+        heapsort_mut(v, compare);
+        return;
+    }
+
+    let (e2, e3, e4, swapped) = choose_pivots(v, compare);
+
+    // If the input appears partially sorted, try an insertion sort.
+    if !swapped && capped_insertion_sort(v, compare) {
+        return;
+    }
+
+    // Dual-pivot quicksort behaves very poorly if both pivots are equal.
+    // Use a single-pivot quicksort if they are.
+    if unsafe { compare_idxs(v, e2, e4, compare) != Equal } {
+        DualPivotSort::dual_pivot_sort(v, (e2, e4), compare, rec, heapsort_depth, abort);
+    } else {
+        // N.B. If compare() is a well-behaved total order,
+        // e3 must be equal to e2 and e4.
+        single_pivot_sort(v, e3, compare, rec, heapsort_depth, abort);
+    }
+}
+
+/// Returns whether the sort was told to stop.
+// `Option::is_some_and` would need Rust 1.70.
+#[allow(clippy::unnecessary_map_or)]
+fn aborted(abort: Option<&Cell<bool>>) -> bool {
+    abort.map_or(false, Cell::get)
+}
+
+/// Like `introsort`, but partitions that do not overlap the non-empty `range`
+/// are not sorted.
+fn introsort_range<T, C: FnMut(&T, &T) -> Ordering>(v: &mut [T], range: Range<usize>, compare: &mut C,
+                                                 rec: u32, heapsort_depth: u32) {
+    if range.start == 0 && range.end == v.len() {
+        introsort(v, compare, rec, heapsort_depth, None);
+        return;
+    }
+
+    if maybe_insertion_sort(v, compare) {
+        return;
+    }
+
+    if rec > heapsort_depth {
+        heapsort_mut(v, compare);
+        return;
+    }
+
+    let (e2, e3, e4, swapped) = choose_pivots(v, compare);
+
+    if !swapped && capped_insertion_sort(v, compare) {
+        return;
+    }
+
+    let n = v.len();
+    if unsafe { compare_idxs(v, e2, e4, compare) != Equal } {
+        let (left_pivot, right_pivot) = DualPivotSort::partition(v, (e2, e4), compare, None);
+        introsort_range_part(&mut v[..left_pivot], 0, &range, compare, rec, heapsort_depth);
+        introsort_range_part(&mut v[left_pivot + 1..right_pivot], left_pivot + 1, &range, compare, rec, heapsort_depth);
+        introsort_range_part(&mut v[right_pivot + 1..], right_pivot + 1, &range, compare, rec, heapsort_depth);
+    } else {
+        let (l, r) = fat_partition(v, e3, compare);
+        introsort_range_part(&mut v[..l], 0, &range, compare, rec, heapsort_depth);
+        introsort_range_part(&mut v[n - r..], n - r, &range, compare, rec, heapsort_depth);
+    }
+}
+
+/// Recurse into the partition `v`, which starts at `offset` in its parent,
+/// if it overlaps `range`.
+fn introsort_range_part<T, C: FnMut(&T, &T) -> Ordering>(v: &mut [T], offset: usize, range: &Range<usize>,
+                                                      compare: &mut C, rec: u32, heapsort_depth: u32) {
+    let start = max(range.start, offset);
+    let end = min(range.end, offset + v.len());
+    if start < end {
+        introsort_range(v, start - offset..end - offset, compare, rec + 1, heapsort_depth);
+    }
+}
+
+/// Move the `k`th smallest element of `v` to `v[k]`, with smaller elements
+/// before it and larger elements after it.
+///
+/// Like `do_introsort`, but only the partition containing `k` is processed.
+/// After `heapsort_depth` partitioning steps it switches to `heapselect`.
+fn introselect<T, C: FnMut(&T, &T) -> Ordering>(mut v: &mut [T], mut k: usize, compare: &mut C, heapsort_depth: u32) {
+    let mut rec = 0;
+    loop {
+        if maybe_insertion_sort(v, compare) {
+            return;
+        }
+
+        if rec > heapsort_depth {
+            heapselect(v, k, compare);
+            return;
+        }
+
+        let (e2, e3, e4, swapped) = choose_pivots(v, compare);
+
+        if !swapped && capped_insertion_sort(v, compare) {
+            return;
+        }
+
+        let n = v.len();
+        let (lo, hi) = if unsafe { compare_idxs(v, e2, e4, compare) != Equal } {
+            let (left_pivot, right_pivot) = DualPivotSort::partition(v, (e2, e4), compare, None);
+            if k < left_pivot {
+                (0, left_pivot)
+            } else if k == left_pivot || k == right_pivot {
+                return;
+            } else if k < right_pivot {
+                (left_pivot + 1, right_pivot)
+            } else {
+                (right_pivot + 1, n)
+            }
+        } else {
+            let (l, r) = fat_partition(v, e3, compare);
+            if k < l {
+                (0, l)
+            } else if k >= n - r {
+                (n - r, n)
+            } else {
+                // `k` is among the elements equal to the pivot.
+                return;
+            }
+        };
+
+        let rest = v;
+        v = &mut rest[lo..hi];
+        k -= lo;
+        rec += 1;
+    }
+}
+
+/// Pick pivot candidates for partitioning `v`.
+/// Returns the indices of the second, third and fourth smallest of five
+/// sampled elements, and whether the samples were out of order.
+pub(crate) fn choose_pivots<T, C: FnMut(&T, &T) -> Ordering>(v: &[T], compare: &mut C) -> (usize, usize, usize, bool) {
     macro_rules! maybe_swap(
         ($v: expr, $a: expr, $b: expr, $compare: expr, $swapped: ident) => {
             if compare_idxs($v, *$a, *$b, $compare) == Greater {
@@ -86,11 +493,6 @@ fn do_introsort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C, rec: u32
         }
     );
 
-    if rec > heapsort_depth {
-        heapsort(v, compare);
-        return;
-    }
-
     let n = v.len();
 
     // Pivot selection algorithm based on Java's DualPivotQuicksort.
@@ -119,23 +521,10 @@ fn do_introsort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C, rec: u32
         maybe_swap!(v, &mut e2, &mut e3, compare, swapped);
     }
 
-    // If the input appears partially sorted, try an insertion sort.
-    if !swapped && capped_insertion_sort(v, compare) {
-        return;
-    }
-
-    // Dual-pivot quicksort behaves very poorly if both pivots are equal.
-    // Use a single-pivot quicksort if they are.
-    if unsafe { compare_idxs(v, e2, e4, compare) != Equal } {
-        DualPivotSort::dual_pivot_sort(v, (e2, e4), compare, rec, heapsort_depth);
-    } else {
-        // N.B. If compare() is a well-behaved total order,
-        // e3 must be equal to e2 and e4.
-        single_pivot_sort(v, e3, compare, rec, heapsort_depth);
-    }
+    (e2, e3, e4, swapped)
 }
 
-fn maybe_insertion_sort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C) -> bool {
+pub(crate) fn maybe_insertion_sort<T, C: FnMut(&T, &T) -> Ordering>(v: &mut [T], compare: &mut C) -> bool {
     let n = v.len();
     if n <= 1 {
         return true;
@@ -144,13 +533,13 @@ fn maybe_insertion_sort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C)
     let threshold = min(MAX_INSERTION_SORT_ELEMS,
                         max(MIN_QUICKSORT_ELEMS, INSERTION_SORT_FACTOR / size_of::<T>()));
     if n <= threshold {
-        insertion_sort(v, compare);
+        insertion_sort_mut(v, compare);
         return true;
     }
-    return false;
+    false
 }
 
-fn capped_insertion_sort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C) -> bool {
+pub(crate) fn capped_insertion_sort<T, C: FnMut(&T, &T) -> Ordering>(v: &mut [T], compare: &mut C) -> bool {
     let mut i = 1;
     let mut cap = INSERTION_SORTED_CAP;
     let n = v.len();
@@ -173,6 +562,14 @@ fn capped_insertion_sort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C)
 ///
 /// `sort` falls back to this for small partitions.
 pub fn insertion_sort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C) {
+    insertion_sort_mut(v, &mut |a, b| compare(a, b));
+}
+
+/// Perform an insertion sort using a comparison function that may mutate
+/// its state.
+///
+/// See `sort_by_mut`.
+pub fn insertion_sort_mut<T, C: FnMut(&T, &T) -> Ordering>(v: &mut [T], compare: &mut C) {
     let mut i = 1;
     let n = v.len();
     while i < n {
@@ -185,7 +582,7 @@ pub fn insertion_sort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C) {
     }
 }
 
-struct DualPivotSort<'a, T: 'a> {
+pub(crate) struct DualPivotSort<'a, T: 'a> {
     p1: usize,
     pivot1: NoDrop<T>,
     p2: usize,
@@ -194,8 +591,24 @@ struct DualPivotSort<'a, T: 'a> {
 }
 
 impl<'a, T: 'a> DualPivotSort<'a, T> {
-    fn dual_pivot_sort<C: Fn(&T, &T) -> Ordering>(v: &mut [T], (p1, p2): (usize, usize),
-                                                  compare: &C, rec: u32, heapsort_depth: u32) {
+    fn dual_pivot_sort<C: FnMut(&T, &T) -> Ordering>(v: &mut [T], pivots: (usize, usize),
+                                                  compare: &mut C, rec: u32, heapsort_depth: u32,
+                                                  abort: Option<&Cell<bool>>) {
+        let (left_pivot, right_pivot) = DualPivotSort::partition(v, pivots, compare, abort);
+        introsort(&mut v[..left_pivot], compare, rec + 1, heapsort_depth, abort);
+        introsort(&mut v[left_pivot + 1..right_pivot], compare, rec + 1, heapsort_depth, abort);
+        introsort(&mut v[right_pivot + 1..], compare, rec + 1, heapsort_depth, abort);
+    }
+
+    /// Partitions `v` around the elements at `p1` and `p2`.
+    /// After partitioning, the array looks as following:
+    /// [ < p1 | p1 | p1 <= x <= p2 | p2 | > p2 ]
+    /// Returns the final positions of the two pivots.
+    ///
+    /// The "assert_working_compare" checks are skipped if there is an
+    /// `abort` flag, as the comparisons of such a sort may be made up.
+    pub(crate) fn partition<C: FnMut(&T, &T) -> Ordering>(v: &mut [T], (p1, p2): (usize, usize), compare: &mut C,
+                                                          abort: Option<&Cell<bool>>) -> (usize, usize) {
         debug_assert!(v.len() > 9);
         let (left, right) = unsafe {
             if compare_idxs(v, p1, p2, compare) == Greater {
@@ -208,11 +621,11 @@ impl<'a, T: 'a> DualPivotSort<'a, T> {
             // otherwise p1 and p2 will be replaced with the last locations in the leftmost and rightmost
             // partitions, where the pivots will be placed.
             let mut this = DualPivotSort{
-                p1: p1,
+                p1,
                 pivot1: NoDrop::new(ptr::read(v.get_unchecked(p1))),
-                p2: p2,
+                p2,
                 pivot2: NoDrop::new(ptr::read(v.get_unchecked(p2))),
-                v: v,
+                v,
             };
             ptr::copy(this.v.get_unchecked(0), this.v.get_unchecked_mut(p1), 1);
             ptr::copy(this.v.get_unchecked(this.v.len() - 1), this.v.get_unchecked_mut(p2), 1);
@@ -231,7 +644,7 @@ impl<'a, T: 'a> DualPivotSort<'a, T> {
                 debug_assert!(l == m || l < r);
                 debug_assert!(r != v.len() - 1);
                 debug_assert!(m < v.len() && r < v.len() && l < v.len());
-                if cfg!(feature="assert_working_compare") {
+                if cfg!(feature="assert_working_compare") && abort.is_none() {
                     debug_assert!(l == m || compare(&v[l], p1) != Less);
                     debug_assert!(l == 1 || compare(&v[l-1], p1) != Greater);
                     debug_assert!(l <= 2 || compare(&v[l-2], p1) != Greater);
@@ -269,7 +682,7 @@ impl<'a, T: 'a> DualPivotSort<'a, T> {
         let left_pivot = left - 1;
         let right_pivot = right + 1;
         debug_assert!(right_pivot > left_pivot);
-        if cfg!(feature="assert_working_compare") {
+        if cfg!(feature="assert_working_compare") && abort.is_none() {
             for item in &v[..left_pivot] {
                 debug_assert!(compare(item, &v[left_pivot]) != Greater);
                 debug_assert!(compare(item, &v[right_pivot]) != Greater);
@@ -284,9 +697,7 @@ impl<'a, T: 'a> DualPivotSort<'a, T> {
             }
             debug_assert!(compare(&v[left_pivot], &v[right_pivot]) == Less);
         }
-        introsort(&mut v[..left_pivot], compare, rec + 1, heapsort_depth);
-        introsort(&mut v[left_pivot + 1..right_pivot], compare, rec + 1, heapsort_depth);
-        introsort(&mut v[right_pivot + 1..], compare, rec + 1, heapsort_depth);
+        (left_pivot, right_pivot)
     }
     unsafe fn write_pivots(&mut self) {
         let n = self.v.len();
@@ -305,14 +716,15 @@ impl<'a, T: 'a> Drop for DualPivotSort<'a, T> {
     }
 }
 
-fn single_pivot_sort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], pivot: usize, compare: &C, rec: u32, heapsort_depth: u32) {
+fn single_pivot_sort<T, C: FnMut(&T, &T) -> Ordering>(v: &mut [T], pivot: usize, compare: &mut C, rec: u32, heapsort_depth: u32,
+                                                      abort: Option<&Cell<bool>>) {
     let (l, r) = fat_partition(v, pivot, compare);
     let n = v.len();
     if l > 1 {
-        introsort(&mut v[..l], compare, rec + 1, heapsort_depth);
+        introsort(&mut v[..l], compare, rec + 1, heapsort_depth, abort);
     }
     if r > 1 {
-        introsort(&mut v[n - r..], compare, rec + 1, heapsort_depth);
+        introsort(&mut v[n - r..], compare, rec + 1, heapsort_depth, abort);
     }
 }
 
@@ -320,7 +732,7 @@ fn single_pivot_sort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], pivot: usize, co
 /// After partitioning, the array looks as following:
 /// <<<<<==>>>
 /// Return (number of < elements, number of > elements)
-fn fat_partition<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], pivot: usize, compare: &C) -> (usize, usize)  {
+pub(crate) fn fat_partition<T, C: FnMut(&T, &T) -> Ordering>(v: &mut [T], pivot: usize, compare: &mut C) -> (usize, usize)  {
     let mut a = 0;
     let mut b = a;
     let mut c = v.len() - 1;
@@ -357,7 +769,7 @@ fn fat_partition<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], pivot: usize, compar
     let r = min(d - c, n - 1 - d);
     unsafe { swap_many(v, b, n - r, r); }
 
-    return (b - a, d - c);
+    (b - a, d - c)
 }
 
 unsafe fn swap_many<T>(v: &mut [T], a: usize, b: usize, n: usize) {
@@ -371,9 +783,18 @@ unsafe fn swap_many<T>(v: &mut [T], a: usize, b: usize, n: usize) {
 /// Perform a heapsort using a comparison function.
 ///
 /// `sort` falls back to this for pathological cases.
+pub fn heapsort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C) {
+    heapsort_mut(v, &mut |a, b| compare(a, b));
+}
+
+/// Perform a heapsort using a comparison function that may mutate its
+/// state.
+///
+/// See `sort_by_mut`.
 #[cold]
 #[inline(never)]
-pub fn heapsort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C) {
+pub fn heapsort_mut<T, C: FnMut(&T, &T) -> Ordering>(v: &mut [T], compare: &mut C) {
+    if v.len() <= 1 { return; }
     let mut end = v.len() as isize;
     heapify(v, compare);
     while end > 0 {
@@ -383,7 +804,39 @@ pub fn heapsort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C) {
     }
 }
 
-fn heapify<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C) {
+/// Move the `k`th smallest element of `v` to `v[k]` using a heap.
+///
+/// The heap is built over whichever side of `k` is smaller, so this takes
+/// O(n log(min(k, n - k))) time.
+#[cold]
+#[inline(never)]
+fn heapselect<T, C: FnMut(&T, &T) -> Ordering>(v: &mut [T], k: usize, compare: &mut C) {
+    let n = v.len();
+    if 2 * k + 1 > n {
+        // Mirror the problem so that the heap holds the largest elements.
+        v.reverse();
+        heapselect_smallest(v, n - 1 - k, &mut |a: &T, b: &T| compare(b, a));
+        v.reverse();
+    } else {
+        heapselect_smallest(v, k, compare);
+    }
+}
+
+/// Keep the `k + 1` smallest elements in a max-heap at the front of `v`,
+/// then move the largest of them to `v[k]`.
+fn heapselect_smallest<T, C: FnMut(&T, &T) -> Ordering>(v: &mut [T], k: usize, compare: &mut C) {
+    let heap_len = k + 1;
+    heapify(&mut v[..heap_len], compare);
+    for i in heap_len..v.len() {
+        if compare_idxs_safe(v, i, 0, compare) == Less {
+            v.swap(0, i);
+            Siftdown::siftdown(&mut v[..heap_len], 0, compare);
+        }
+    }
+    v.swap(0, k);
+}
+
+fn heapify<T, C: FnMut(&T, &T) -> Ordering>(v: &mut [T], compare: &mut C) {
     let mut n = (v.len() as isize).wrapping_sub(1) / 4;
     while n >= 0 {
         Siftdown::siftdown(v, n as usize, compare);
@@ -398,7 +851,7 @@ struct Siftup<'a, T: 'a> {
 }
 
 impl<'a, T: 'a> Siftup<'a, T> {
-    fn siftup<C: Fn(&T, &T) -> Ordering>(v_: &mut [T], start: usize, pos_: usize, compare: &C) {
+    fn siftup<C: FnMut(&T, &T) -> Ordering>(v_: &mut [T], start: usize, pos_: usize, compare: &mut C) {
         unsafe {
             let mut this = Siftup{
                 new: NoDrop::new(ptr::read(v_.get_unchecked_mut(pos_))),
@@ -432,7 +885,7 @@ struct Siftdown<'a, T: 'a> {
 }
 
 impl<'a, T: 'a> Siftdown<'a, T> {
-    fn siftdown_range<C: Fn(&T, &T) -> Ordering>(v_: &mut [T], pos_: usize, end: usize, compare: &C) {
+    fn siftdown_range<C: FnMut(&T, &T) -> Ordering>(v_: &mut [T], pos_: usize, end: usize, compare: &mut C) {
         let pos = unsafe {
             let mut this = Siftdown{
                 new: NoDrop::new(ptr::read(v_.get_unchecked_mut(pos_))),
@@ -478,7 +931,7 @@ impl<'a, T: 'a> Siftdown<'a, T> {
         Siftup::siftup(v_, pos_, pos, compare);
     }
 
-    fn siftdown<C: Fn(&T, &T) -> Ordering>(v: &mut [T], pos: usize, compare: &C) {
+    fn siftdown<C: FnMut(&T, &T) -> Ordering>(v: &mut [T], pos: usize, compare: &mut C) {
         let len = v.len();
         Siftdown::siftdown_range(v, pos, len, compare);
     }
@@ -492,21 +945,537 @@ impl<'a, T: 'a> Drop for Siftdown<'a, T> {
     }
 }
 
-fn log2(x: usize) -> u32 {
+/// The smallest groups that `block_merge_sort` insertion sorts before it
+/// starts merging.
+const BLOCK_MERGE_MIN_LEVEL: usize = 16;
+
+/// A half-open range of indices, used by `block_merge_sort`.
+#[derive(Clone, Copy)]
+struct Span {
+    start: usize,
+    end: usize,
+}
+
+impl Span {
+    fn new(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+/// Walks the pairs of subarrays that are merged at one level of a bottom-up
+/// merge sort.
+///
+/// The slice length doesn't have to be a power of two: the subarrays at each
+/// level differ in length by at most one, which is tracked as a fraction.
+struct MergeLevels {
+    size: usize,
+    numerator: usize,
+    decimal: usize,
+    denominator: usize,
+    decimal_step: usize,
+    numerator_step: usize,
+}
+
+impl MergeLevels {
+    fn new(size: usize, min_level: usize) -> MergeLevels {
+        let power_of_two = 1 << (log2(size) - 1);
+        let denominator = power_of_two / min_level;
+        MergeLevels {
+            size,
+            numerator: 0,
+            decimal: 0,
+            denominator,
+            decimal_step: size / denominator,
+            numerator_step: size % denominator,
+        }
+    }
+
+    fn begin(&mut self) {
+        self.numerator = 0;
+        self.decimal = 0;
+    }
+
+    fn next_range(&mut self) -> Span {
+        let start = self.decimal;
+        self.decimal += self.decimal_step;
+        self.numerator += self.numerator_step;
+        if self.numerator >= self.denominator {
+            self.numerator -= self.denominator;
+            self.decimal += 1;
+        }
+        Span::new(start, self.decimal)
+    }
+
+    fn finished(&self) -> bool {
+        self.decimal >= self.size
+    }
+
+    fn next_level(&mut self) -> bool {
+        self.decimal_step += self.decimal_step;
+        self.numerator_step += self.numerator_step;
+        if self.numerator_step >= self.denominator {
+            self.numerator_step -= self.denominator;
+            self.decimal_step += 1;
+        }
+        self.decimal_step < self.size
+    }
+
+    /// The length of the shorter subarrays at this level.
+    fn length(&self) -> usize {
+        self.decimal_step
+    }
+}
+
+/// Where the values of one internal buffer were pulled out from, and where
+/// they must be redistributed to.
+#[derive(Clone, Copy)]
+struct Pull {
+    from: usize,
+    to: usize,
+    count: usize,
+    range: Span,
+}
+
+/// Stable in-place merge sort.
+///
+/// This is a port of WikiSort without its cache. Each level pulls out two
+/// internal buffers of unique values; the first one tags the A blocks as
+/// they are rolled through B, and the second one is swap space for the local
+/// merges. Every element is only ever moved by swaps and rotations, so `v`
+/// is a permutation of its original contents even if `compare` unwinds.
+fn block_merge_sort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C) {
+    let size = v.len();
+    if size < 2 * BLOCK_MERGE_MIN_LEVEL {
+        insertion_sort(v, compare);
+        return;
+    }
+
+    let mut levels = MergeLevels::new(size, BLOCK_MERGE_MIN_LEVEL);
+    while !levels.finished() {
+        let range = levels.next_range();
+        insertion_sort(&mut v[range.start..range.end], compare);
+    }
+
+    loop {
+        block_merge_level(v, &mut levels, compare);
+        if !levels.next_level() {
+            break;
+        }
+    }
+}
+
+/// Merge every pair of subarrays at the current level of `levels`.
+fn block_merge_level<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], levels: &mut MergeLevels, compare: &C) {
+    let length = levels.length();
+    let mut block_size = isqrt(length);
+    let mut buffer_size = length / block_size + 1;
+
+    // Look for a single run of 2√A unique values, or two separate runs of √A
+    // unique values, to use as the internal buffers. If neither exists, use
+    // the largest run found, and merge without the second buffer.
+    let mut buffer1 = Span::new(0, 0);
+    let mut buffer2 = Span::new(0, 0);
+    let mut pull = [Pull { from: 0, to: 0, count: 0, range: Span::new(0, 0) }; 2];
+    let mut pull_index = 0;
+    let mut find = buffer_size + buffer_size;
+    let mut find_separately = false;
+    if find > length {
+        // Both buffers can't fit into the same subarray.
+        find = buffer_size;
+        find_separately = true;
+    }
+
+    levels.begin();
+    while !levels.finished() {
+        let a = levels.next_range();
+        let b = levels.next_range();
+
+        // Count the unique values in A; they will be pulled to its start.
+        let mut last = a.start;
+        let mut count = 1;
+        while count < find {
+            let index = binary_last(v, &v[last], Span::new(last + 1, a.end), compare);
+            if index == a.end {
+                break;
+            }
+            last = index;
+            count += 1;
+        }
+
+        if count >= buffer_size {
+            pull[pull_index] = Pull { from: last, to: a.start, count, range: Span::new(a.start, b.end) };
+            pull_index = 1;
+            if count == buffer_size + buffer_size {
+                buffer1 = Span::new(a.start, a.start + buffer_size);
+                buffer2 = Span::new(a.start + buffer_size, a.start + count);
+                break;
+            } else if find == buffer_size + buffer_size {
+                buffer1 = Span::new(a.start, a.start + count);
+                find = buffer_size;
+            } else if find_separately {
+                buffer1 = Span::new(a.start, a.start + count);
+                find_separately = false;
+            } else {
+                buffer2 = Span::new(a.start, a.start + count);
+                break;
+            }
+        } else if pull_index == 0 && count > buffer1.len() {
+            buffer1 = Span::new(a.start, a.start + count);
+            pull[0] = Pull { from: last, to: a.start, count, range: Span::new(a.start, b.end) };
+        }
+
+        // Count the unique values in B; they will be pulled to its end.
+        let mut last = b.end - 1;
+        let mut count = 1;
+        while count < find {
+            let index = binary_first(v, &v[last], Span::new(b.start, last), compare);
+            if index == b.start {
+                break;
+            }
+            last = index - 1;
+            count += 1;
+        }
+
+        if count >= buffer_size {
+            pull[pull_index] = Pull { from: last, to: b.end, count, range: Span::new(a.start, b.end) };
+            pull_index = 1;
+            if count == buffer_size + buffer_size {
+                buffer1 = Span::new(b.end - count, b.end - buffer_size);
+                buffer2 = Span::new(b.end - buffer_size, b.end);
+                break;
+            } else if find == buffer_size + buffer_size {
+                buffer1 = Span::new(b.end - count, b.end);
+                find = buffer_size;
+            } else if find_separately {
+                buffer1 = Span::new(b.end - count, b.end);
+                find_separately = false;
+            } else {
+                // If the first buffer came from the A half of this pair, it
+                // must not be redistributed into the second one.
+                if pull[0].range.start == a.start {
+                    pull[0].range.end -= pull[1].count;
+                }
+                buffer2 = Span::new(b.end - count, b.end);
+                break;
+            }
+        } else if pull_index == 0 && count > buffer1.len() {
+            buffer1 = Span::new(b.end - count, b.end);
+            pull[0] = Pull { from: last, to: b.end, count, range: Span::new(a.start, b.end) };
+        }
+    }
+
+    // Pull the unique values out into contiguous buffers.
+    for p in pull.iter_mut() {
+        if p.to < p.from {
+            let mut index = p.from;
+            for count in 1..p.count {
+                index = binary_first(v, &v[index - 1], Span::new(p.to, p.from - (count - 1)), compare);
+                let range = Span::new(index + 1, p.from + 1);
+                rotate(v, range.len() - count, range);
+                p.from = index + count;
+            }
+        } else if p.to > p.from {
+            let mut index = p.from + 1;
+            for count in 1..p.count {
+                index = binary_last(v, &v[index], Span::new(index, p.to), compare);
+                let range = Span::new(p.from, index - 1);
+                rotate(v, count, range);
+                p.from = index - 1 - count;
+            }
+        }
+    }
+
+    // There must be a tag in the first buffer for every A block.
+    buffer_size = buffer1.len();
+    block_size = length / buffer_size + 1;
+
+    levels.begin();
+    'pairs: while !levels.finished() {
+        let mut a = levels.next_range();
+        let mut b = levels.next_range();
+
+        // Leave out the parts of A and B that hold the internal buffers.
+        let start = a.start;
+        for p in &pull {
+            if start == p.range.start {
+                if p.from > p.to {
+                    a.start += p.count;
+                    if a.len() == 0 {
+                        continue 'pairs;
+                    }
+                } else if p.from < p.to {
+                    b.end -= p.count;
+                    if b.len() == 0 {
+                        continue 'pairs;
+                    }
+                }
+            }
+        }
+
+        if compare(&v[b.end - 1], &v[a.start]) == Less {
+            // The two ranges are in reverse order, so a rotation merges them.
+            rotate(v, a.len(), Span::new(a.start, b.end));
+        } else if compare(&v[a.end], &v[a.end - 1]) == Less {
+            // Break A into blocks, where the first one may be shorter.
+            let mut block_a = Span::new(a.start, a.end);
+            let first_a = Span::new(a.start, a.start + block_a.len() % block_size);
+
+            // Tag each A block by swapping its first value into buffer1.
+            let mut index_a = buffer1.start;
+            let mut index = first_a.end;
+            while index < block_a.end {
+                v.swap(index_a, index);
+                index_a += 1;
+                index += block_size;
+            }
+
+            // Roll the A blocks through the B blocks. Whenever an A block is
+            // dropped behind, merge the previous A block with the B values
+            // that follow it.
+            let mut last_a = first_a;
+            let mut last_b = Span::new(0, 0);
+            let mut block_b = Span::new(b.start, b.start + min(block_size, b.len()));
+            block_a.start += first_a.len();
+            index_a = buffer1.start;
+
+            if buffer2.len() > 0 {
+                block_swap(v, last_a.start, buffer2.start, last_a.len());
+            }
+
+            if block_a.len() > 0 {
+                loop {
+                    if (last_b.len() > 0 && compare(&v[last_b.end - 1], &v[index_a]) != Less) || block_b.len() == 0 {
+                        // Split the previous B block where the smallest A
+                        // block belongs.
+                        let b_split = binary_first(v, &v[index_a], last_b, compare);
+                        let b_remaining = last_b.end - b_split;
+
+                        // The smallest A block is the one with the smallest tag.
+                        let mut min_a = block_a.start;
+                        let mut find_a = min_a + block_size;
+                        while find_a < block_a.end {
+                            if compare(&v[find_a], &v[min_a]) == Less {
+                                min_a = find_a;
+                            }
+                            find_a += block_size;
+                        }
+                        block_swap(v, block_a.start, min_a, block_size);
+
+                        // Restore its first value from buffer1.
+                        v.swap(block_a.start, index_a);
+                        index_a += 1;
+
+                        merge_block(v, last_a, Span::new(last_a.end, b_split), buffer2, compare);
+
+                        if buffer2.len() > 0 {
+                            // Park the A block in buffer2, where the next merge
+                            // expects it. Whatever takes its place is scratch
+                            // space, so B can be block swapped instead of rotated.
+                            block_swap(v, block_a.start, buffer2.start, block_size);
+                            block_swap(v, b_split, block_a.start + block_size - b_remaining, b_remaining);
+                        } else {
+                            rotate(v, block_a.start - b_split, Span::new(b_split, block_a.start + block_size));
+                        }
+
+                        last_a = Span::new(block_a.start - b_remaining, block_a.start - b_remaining + block_size);
+                        last_b = Span::new(last_a.end, last_a.end + b_remaining);
+
+                        block_a.start += block_size;
+                        if block_a.len() == 0 {
+                            break;
+                        }
+                    } else if block_b.len() < block_size {
+                        // Move the last, shorter B block in front of the
+                        // remaining A blocks.
+                        rotate(v, block_b.start - block_a.start, Span::new(block_a.start, block_b.end));
+                        last_b = Span::new(block_a.start, block_a.start + block_b.len());
+                        block_a.start += block_b.len();
+                        block_a.end += block_b.len();
+                        block_b.end = block_b.start;
+                    } else {
+                        // Roll the leftmost A block past the next B block.
+                        block_swap(v, block_a.start, block_b.start, block_size);
+                        last_b = Span::new(block_a.start, block_a.start + block_size);
+                        block_a.start += block_size;
+                        block_a.end += block_size;
+                        block_b.start += block_size;
+                        if block_b.end > b.end - block_size {
+                            block_b.end = b.end;
+                        } else {
+                            block_b.end += block_size;
+                        }
+                    }
+                }
+            }
+
+            merge_block(v, last_a, Span::new(last_a.end, b.end), buffer2, compare);
+        }
+    }
+
+    // buffer2 was used as swap space and is out of order. Its values are
+    // unique, so sorting it restores its original order.
+    insertion_sort(&mut v[buffer2.start..buffer2.end], compare);
+
+    // Redistribute the buffers back into the array, reversing the pull.
+    for p in &pull {
+        if p.from > p.to {
+            let mut buffer = Span::new(p.range.start, p.range.start + p.count);
+            while buffer.len() > 0 {
+                let index = binary_first(v, &v[buffer.start], Span::new(buffer.end, p.range.end), compare);
+                let amount = index - buffer.end;
+                rotate(v, buffer.len(), Span::new(buffer.start, index));
+                buffer.start += amount + 1;
+                buffer.end += amount;
+            }
+        } else if p.from < p.to {
+            let mut buffer = Span::new(p.range.end - p.count, p.range.end);
+            while buffer.len() > 0 {
+                let index = binary_last(v, &v[buffer.end - 1], Span::new(p.range.start, buffer.start), compare);
+                let amount = buffer.start - index;
+                rotate(v, amount, Span::new(index, buffer.end));
+                buffer.start -= amount;
+                buffer.end -= amount + 1;
+            }
+        }
+    }
+}
+
+/// Merge the A block with the B values that follow it. If `buffer` is not
+/// empty, A's values have been parked in it and `a` is scratch space.
+fn merge_block<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], a: Span, b: Span, buffer: Span, compare: &C) {
+    if buffer.len() > 0 {
+        merge_internal(v, a, b, buffer, compare);
+    } else {
+        merge_in_place(v, a, b, compare);
+    }
+}
+
+/// Merge using an internal buffer that holds A's values. Each merged value
+/// is swapped into place, so the buffer gets its own values back, in a
+/// different order.
+fn merge_internal<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], a: Span, b: Span, buffer: Span, compare: &C) {
+    let mut a_count = 0;
+    let mut b_count = 0;
+    let mut insert = 0;
+    if b.len() > 0 && a.len() > 0 {
+        loop {
+            if compare(&v[b.start + b_count], &v[buffer.start + a_count]) != Less {
+                v.swap(a.start + insert, buffer.start + a_count);
+                a_count += 1;
+                insert += 1;
+                if a_count >= a.len() {
+                    break;
+                }
+            } else {
+                v.swap(a.start + insert, b.start + b_count);
+                b_count += 1;
+                insert += 1;
+                if b_count >= b.len() {
+                    break;
+                }
+            }
+        }
+    }
+    block_swap(v, buffer.start + a_count, a.start + insert, a.len() - a_count);
+}
+
+/// Merge without a buffer, by repeatedly searching for where the start of A
+/// belongs in B and rotating A there.
+///
+/// This is quadratic in general, but it is only used when there are so few
+/// unique values that the number of rotations is bounded.
+fn merge_in_place<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], mut a: Span, mut b: Span, compare: &C) {
+    if a.len() == 0 || b.len() == 0 {
+        return;
+    }
+    loop {
+        let mid = binary_first(v, &v[a.start], b, compare);
+        let amount = mid - a.end;
+        rotate(v, a.len(), Span::new(a.start, mid));
+        if b.end == mid {
+            break;
+        }
+        b.start = mid;
+        a = Span::new(a.start + amount, b.start);
+        a.start = binary_last(v, &v[a.start], a, compare);
+        if a.len() == 0 {
+            break;
+        }
+    }
+}
+
+/// Index of the first element in `v[range]` that is not less than `value`.
+fn binary_first<T, C: Fn(&T, &T) -> Ordering>(v: &[T], value: &T, range: Span, compare: &C) -> usize {
+    let mut start = range.start;
+    let mut end = range.end;
+    while start < end {
+        let mid = start + (end - start) / 2;
+        if compare(&v[mid], value) == Less {
+            start = mid + 1;
+        } else {
+            end = mid;
+        }
+    }
+    start
+}
+
+/// Index of the first element in `v[range]` that is greater than `value`.
+fn binary_last<T, C: Fn(&T, &T) -> Ordering>(v: &[T], value: &T, range: Span, compare: &C) -> usize {
+    let mut start = range.start;
+    let mut end = range.end;
+    while start < end {
+        let mid = start + (end - start) / 2;
+        if compare(value, &v[mid]) != Less {
+            start = mid + 1;
+        } else {
+            end = mid;
+        }
+    }
+    start
+}
+
+fn rotate<T>(v: &mut [T], amount: usize, range: Span) {
+    v[range.start..range.end].rotate_left(amount);
+}
+
+fn block_swap<T>(v: &mut [T], a: usize, b: usize, n: usize) {
+    for i in 0..n {
+        v.swap(a + i, b + i);
+    }
+}
+
+fn isqrt(x: usize) -> usize {
+    if x < 2 {
+        return x;
+    }
+    let mut r = x;
+    let mut next = x / 2 + 1;
+    while next < r {
+        r = next;
+        next = (r + x / r) / 2;
+    }
+    r
+}
+
+pub(crate) fn log2(x: usize) -> u32 {
     if x <= 1 { return 0; }
     let n = x.leading_zeros();
     size_of::<usize>() as u32 * 8 - n
 }
 
 #[inline(always)]
-unsafe fn compare_idxs<T, C: Fn(&T, &T) -> Ordering>(v: &[T], a: usize, b: usize, compare: &C) -> Ordering {
+unsafe fn compare_idxs<T, C: FnMut(&T, &T) -> Ordering>(v: &[T], a: usize, b: usize, compare: &mut C) -> Ordering {
     let x = v.get_unchecked(a);
     let y = v.get_unchecked(b);
     compare(x, y)
 }
 
 #[inline(always)]
-fn compare_idxs_safe<T, C: Fn(&T, &T) -> Ordering>(v: &[T], a: usize, b: usize, compare: &C) -> Ordering {
+fn compare_idxs_safe<T, C: FnMut(&T, &T) -> Ordering>(v: &[T], a: usize, b: usize, compare: &mut C) -> Ordering {
     compare(&v[a], &v[b])
 }
 
@@ -514,3 +1483,25 @@ fn compare_idxs_safe<T, C: Fn(&T, &T) -> Ordering>(v: &[T], a: usize, b: usize,
 unsafe fn unsafe_swap<T>(v: &mut[T], a: usize, b: usize) {
     ptr::swap(v.get_unchecked_mut(a) as *mut T, v.get_unchecked_mut(b) as *mut T);
 }
+
+/// Move the element at index `*source(&mut perm[i])` to index `i` in `v`, for
+/// every `i`.
+///
+/// The sources must be a permutation of `0..v.len()`. They are overwritten
+/// while following the cycles of the permutation.
+#[cfg(feature = "alloc")]
+pub(crate) fn apply_permutation<T, P, S: Fn(&mut P) -> &mut usize>(v: &mut [T], perm: &mut [P], source: S) {
+    for i in 0..v.len() {
+        let mut cur = i;
+        loop {
+            let src = *source(&mut perm[cur]);
+            // Mark `cur` as done.
+            *source(&mut perm[cur]) = cur;
+            if src == i {
+                break;
+            }
+            v.swap(cur, src);
+            cur = src;
+        }
+    }
+}
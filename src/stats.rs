@@ -0,0 +1,225 @@
+// This file is licensed under the same terms as Rust itself.
+
+//! Medians and quantiles of floating point numbers.
+//!
+//! These find the elements they need with `select_nth` instead of sorting
+//! the whole slice, so they reorder the slice they are given. Like
+//! `sort_floats`, they first move the NaNs to the end, and then handle them
+//! as the `NanPolicy` says.
+//!
+//! # Example
+//!
+//!     use quickersort::stats::{median_floats, quantile_floats, Interpolation, NanPolicy};
+//!
+//!     let mut v = [7.0, 1.0, ::std::f64::NAN, 3.0, 4.0];
+//!     assert_eq!(median_floats(&mut v, NanPolicy::Ignore), Ok(3.5));
+//!     assert_eq!(quantile_floats(&mut v, 0.25, Interpolation::Lower, NanPolicy::Ignore), Ok(1.0));
+//!     assert!(median_floats(&mut v, NanPolicy::Propagate).unwrap().is_nan());
+
+use core::fmt;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use super::float::{Float, move_to_end};
+use super::sort::select_nth_by;
+
+/// How to compute a quantile that falls between two elements.
+///
+/// For a quantile `q` of `n` elements, the position in the sorted elements
+/// is `h = (n - 1) * q`, between the elements at `floor(h)` and `ceil(h)`.
+/// These are the methods of the same names in NumPy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Interpolation {
+    /// Interpolate linearly between the two elements.
+    Linear,
+    /// Take the lower element.
+    Lower,
+    /// Take the higher element.
+    Higher,
+    /// Take the nearest element, or the one at an even position on a tie.
+    Nearest,
+    /// Take the average of the two elements.
+    Midpoint,
+}
+
+/// What to do with NaNs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NanPolicy {
+    /// Return NaN if there are any NaNs.
+    Propagate,
+    /// Leave out NaNs.
+    Ignore,
+    /// Return `StatsError::Nan` if there are any NaNs.
+    Error,
+}
+
+/// The reasons a statistic can not be computed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatsError {
+    /// There are no numbers, possibly after leaving out NaNs.
+    Empty,
+    /// There is a NaN, and the `NanPolicy` is `Error`.
+    Nan,
+}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StatsError::Empty => "no numbers to compute a statistic of",
+            StatsError::Nan => "found a NaN",
+        }.fmt(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for StatsError {}
+
+/// Returns the median, the average of the middle two elements if there is
+/// an even number of them.
+///
+/// This is `quantile_floats(v, 0.5, Interpolation::Linear, nans)`.
+pub fn median_floats<T: Float + Into<f64>>(v: &mut [T], nans: NanPolicy) -> Result<f64, StatsError> {
+    quantile_floats(v, 0.5, Interpolation::Linear, nans)
+}
+
+/// Returns the quantile `q` of `v`, where `q` is between 0 and 1.
+///
+/// `v` is reordered such that the elements used are at their sorted
+/// positions among the numbers.
+///
+/// # Panics
+///
+/// Panics if `q` is not between 0 and 1.
+pub fn quantile_floats<T: Float + Into<f64>>(v: &mut [T], q: f64, interpolation: Interpolation, nans: NanPolicy)
+                                             -> Result<f64, StatsError> {
+    let v = match numbers(v, nans)? {
+        Some(v) => v,
+        None => return Ok(f64::NAN),
+    };
+    let (lo, hi, t) = position(v.len(), q);
+    let (_, a, right) = select_nth_by(v, lo, &compare);
+    let a = (*a).into();
+    if hi == lo {
+        return Ok(a);
+    }
+    // The next element is the smallest one to the right.
+    let mut next = 0;
+    for i in 1..right.len() {
+        if right[i] < right[next] {
+            next = i;
+        }
+    }
+    right.swap(0, next);
+    Ok(interpolate(a, right[0].into(), lo, t, interpolation))
+}
+
+/// Returns the quantiles `qs` of `v`, where each `q` is between 0 and 1.
+///
+/// This selects every element that is needed in one pass, which is faster
+/// than calling `quantile_floats` for each `q`. `v` is reordered such that
+/// the elements used are at their sorted positions among the numbers.
+///
+/// # Panics
+///
+/// Panics if any `q` is not between 0 and 1.
+///
+/// # Example
+///
+///     use quickersort::stats::{quantiles_floats, Interpolation, NanPolicy};
+///
+///     let mut v: Vec<f64> = (0..101).map(|x| x as f64).rev().collect();
+///     let qs = quantiles_floats(&mut v, &[0.1, 0.5, 0.995], Interpolation::Linear, NanPolicy::Error);
+///     assert_eq!(qs, Ok(vec![10.0, 50.0, 99.5]));
+#[cfg(feature = "alloc")]
+pub fn quantiles_floats<T: Float + Into<f64>>(v: &mut [T], qs: &[f64], interpolation: Interpolation,
+                                              nans: NanPolicy) -> Result<Vec<f64>, StatsError> {
+    let v = match numbers(v, nans)? {
+        Some(v) => v,
+        None => return Ok(qs.iter().map(|_| f64::NAN).collect()),
+    };
+    let positions: Vec<_> = qs.iter().map(|&q| position(v.len(), q)).collect();
+    let mut indices: Vec<usize> = positions.iter().flat_map(|&(lo, hi, _)| [lo, hi]).collect();
+    indices.sort_unstable();
+    indices.dedup();
+    select_all(v, &indices, 0);
+    Ok(positions.iter().map(|&(lo, hi, t)| {
+        if lo == hi {
+            v[lo].into()
+        } else {
+            interpolate(v[lo].into(), v[hi].into(), lo, t, interpolation)
+        }
+    }).collect())
+}
+
+/// Move the NaNs out of `v`, and return the numbers, or `None` if the result
+/// should be NaN.
+fn numbers<T: Float>(v: &mut [T], nans: NanPolicy) -> Result<Option<&mut [T]>, StatsError> {
+    let end = move_to_end(v, |x: &T| x.is_nan());
+    if end < v.len() {
+        match nans {
+            NanPolicy::Propagate => return Ok(None),
+            NanPolicy::Error => return Err(StatsError::Nan),
+            NanPolicy::Ignore => (),
+        }
+    }
+    if end == 0 {
+        return Err(StatsError::Empty);
+    }
+    Ok(Some(&mut v[..end]))
+}
+
+/// Returns the positions of the elements around quantile `q` of `n`
+/// elements, and how far `q` is between them.
+fn position(n: usize, q: f64) -> (usize, usize, f64) {
+    assert!((0.0..=1.0).contains(&q), "quantile {} is not between 0 and 1", q);
+    let h = (n - 1) as f64 * q;
+    // `h` is not negative, so this rounds down.
+    let lo = h as usize;
+    let t = h - lo as f64;
+    (lo, if t > 0.0 { lo + 1 } else { lo }, t)
+}
+
+/// Returns the value `t` of the way from `a` at position `lo` to `b` at
+/// position `lo + 1`, by the given method.
+fn interpolate(a: f64, b: f64, lo: usize, t: f64, interpolation: Interpolation) -> f64 {
+    match interpolation {
+        Interpolation::Linear => lerp(a, b, t),
+        Interpolation::Lower => a,
+        Interpolation::Higher => b,
+        // Round the position half to even, like NumPy.
+        Interpolation::Nearest => if t < 0.5 || (t == 0.5 && lo & 1 == 0) { a } else { b },
+        Interpolation::Midpoint => lerp(a, b, 0.5),
+    }
+}
+
+/// Linear interpolation, computed from the closer end as NumPy does.
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    if a == b {
+        // Also correct for infinities, where `b - a` is NaN.
+        return a;
+    }
+    // Unlike NumPy, avoid computing `inf - inf` when `b` is infinite.
+    if t < 0.5 || b.is_infinite() {
+        a + (b - a) * t
+    } else {
+        b - (b - a) * (1.0 - t)
+    }
+}
+
+/// Move the element belonging at each of the sorted `indices` into place.
+/// `offset` is the position of `v` in the whole slice.
+#[cfg(feature = "alloc")]
+fn select_all<T: Float>(v: &mut [T], indices: &[usize], offset: usize) {
+    if indices.is_empty() {
+        return;
+    }
+    let mid = indices.len() / 2;
+    let k = indices[mid] - offset;
+    let (left, _, right) = select_nth_by(v, k, &compare);
+    select_all(left, &indices[..mid], offset);
+    select_all(right, &indices[mid + 1..], offset + k + 1);
+}
+
+/// Compares numbers that are not NaN.
+fn compare<T: Float>(x: &T, y: &T) -> ::core::cmp::Ordering {
+    x.partial_cmp(y).unwrap_or(::core::cmp::Ordering::Equal)
+}
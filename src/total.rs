@@ -0,0 +1,120 @@
+// This file is licensed under the same terms as Rust itself.
+
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::slice;
+use super::float::Float;
+use super::radix::RadixKey;
+
+/// A floating point number that is totally ordered, in the order used by
+/// `sort_floats`:
+/// | -inf | < 0 | -0 | +0 | > 0 | +inf | NaN |
+///
+/// All NaNs are equal to each other, and `-0` and `+0` are not equal. `Eq`,
+/// `Ord` and `Hash` all agree with this, so `Total` can be used as a key in
+/// a `BTreeMap` or `HashMap`, in a `BinaryHeap`, or with `sort`.
+///
+/// `Total<T>` has the same layout as `T`, so slices can be converted in
+/// both directions without copying.
+///
+/// # Example
+///
+///     use quickersort::{Total, TotalF64};
+///     use std::f64::NAN;
+///
+///     let mut v = [2.0, NAN, -0.0, 0.0, -1.0];
+///     quickersort::sort(TotalF64::from_mut_slice(&mut v));
+///     assert_eq!(v[..4], [-1.0, -0.0, 0.0, 2.0]);
+///     assert!(v[1].is_sign_negative() && v[4].is_nan());
+///
+///     assert!(Total(NAN) == Total(-NAN));
+///     assert!(Total(-0.0) < Total(0.0));
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Total<T: Float>(pub T);
+
+/// A totally ordered `f32`.
+pub type TotalF32 = Total<f32>;
+
+/// A totally ordered `f64`.
+pub type TotalF64 = Total<f64>;
+
+impl<T: Float> Total<T> {
+    /// Views a slice of floats as a slice of `Total`.
+    pub fn from_slice(v: &[T]) -> &[Total<T>] {
+        // `Total<T>` is a `repr(transparent)` wrapper around `T`.
+        unsafe { slice::from_raw_parts(v.as_ptr() as *const Total<T>, v.len()) }
+    }
+
+    /// Views a mutable slice of floats as a mutable slice of `Total`.
+    pub fn from_mut_slice(v: &mut [T]) -> &mut [Total<T>] {
+        unsafe { slice::from_raw_parts_mut(v.as_mut_ptr() as *mut Total<T>, v.len()) }
+    }
+
+    /// Views a slice of `Total` as a slice of floats.
+    pub fn as_floats(v: &[Total<T>]) -> &[T] {
+        unsafe { slice::from_raw_parts(v.as_ptr() as *const T, v.len()) }
+    }
+
+    /// Views a mutable slice of `Total` as a mutable slice of floats.
+    pub fn as_mut_floats(v: &mut [Total<T>]) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(v.as_mut_ptr() as *mut T, v.len()) }
+    }
+}
+
+impl<T: Float> From<T> for Total<T> {
+    fn from(x: T) -> Total<T> {
+        Total(x)
+    }
+}
+
+impl<T: Float> Ord for Total<T> {
+    #[inline]
+    fn cmp(&self, other: &Total<T>) -> Ordering {
+        match (self.0.is_nan(), other.0.is_nan()) {
+            (false, false) => self.0.order_key().cmp(&other.0.order_key()),
+            (a, b) => a.cmp(&b),
+        }
+    }
+}
+
+impl<T: Float> PartialOrd for Total<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Total<T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Float> PartialEq for Total<T> {
+    #[inline]
+    fn eq(&self, other: &Total<T>) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Float> Eq for Total<T> {}
+
+impl<T: Float> Hash for Total<T> where T::Bits: Hash {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        if self.0.is_nan() {
+            state.write_u8(1);
+        } else {
+            state.write_u8(0);
+            self.0.order_key().hash(state);
+        }
+    }
+}
+
+impl<T: Float> RadixKey for Total<T> {
+    const BYTES: usize = T::Bits::BYTES;
+
+    #[inline]
+    fn key_byte(&self, i: usize) -> u8 {
+        // No number has a key of all ones, so this puts NaNs last.
+        if self.0.is_nan() {
+            0xff
+        } else {
+            self.0.order_key().key_byte(i)
+        }
+    }
+}
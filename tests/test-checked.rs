@@ -0,0 +1,114 @@
+#![cfg(feature = "alloc")]
+
+extern crate quickersort;
+extern crate rand;
+
+use std::cmp::Ordering;
+use std::cmp::Ordering::*;
+use quickersort::{checked_sort, checked_sort_by, checked_sort_by_key, ComparatorErrorKind};
+use rand::{Rng, weak_rng};
+
+// Check that the comparisons in `err` are what `compare` returns.
+fn check_comparisons<T, C: Fn(&T, &T) -> Ordering>(v: &[T], err: &quickersort::ComparatorError, compare: &C) {
+    for &(i, j, ordering) in err.comparisons() {
+        assert_eq!(compare(&v[i], &v[j]), ordering);
+    }
+}
+
+#[test]
+fn test_checked_sort() {
+    let mut rng = weak_rng();
+    for &len in &[0, 1, 2, 10, 100, 1000, 10000] {
+        for &modulus in &[5, 1_000_000] {
+            let mut v: Vec<u32> = rng.gen_iter::<u32>().map(|x| x % modulus).take(len).collect();
+            let mut expected = v.clone();
+            expected.sort();
+            assert_eq!(checked_sort(&mut v), Ok(()));
+            assert_eq!(v, expected);
+        }
+    }
+}
+
+#[test]
+fn test_checked_sort_by_key() {
+    let mut v = vec![(1, 'c'), (0, 'b'), (2, 'a')];
+    assert_eq!(checked_sort_by_key(&mut v, |p| p.1), Ok(()));
+    assert_eq!(v, [(2, 'a'), (0, 'b'), (1, 'c')]);
+}
+
+#[test]
+fn test_checked_sort_antisymmetry() {
+    // Ties compare as less both ways.
+    let compare = |a: &u32, b: &u32| if a / 10 == b / 10 { Less } else { a.cmp(b) };
+    let mut v: Vec<u32> = weak_rng().gen_iter::<u32>().map(|x| x % 1000).take(500).collect();
+    let original = v.clone();
+    let err = checked_sort_by(&mut v, &compare).unwrap_err();
+    assert_eq!(v, original);
+    assert_eq!(err.kind(), ComparatorErrorKind::Antisymmetry);
+    assert_eq!(err.comparisons().len(), 2);
+    let (i, j, _) = err.comparisons()[0];
+    assert_eq!(original[i] / 10, original[j] / 10);
+    assert_eq!(err.comparisons()[1].0, j);
+    check_comparisons(&original, &err, &compare);
+}
+
+#[test]
+fn test_checked_sort_reflexivity() {
+    let mut v = [1, 2, 3, 3];
+    let err = checked_sort_by(&mut v, &|a, b| if a == b { Greater } else { a.cmp(b) }).unwrap_err();
+    assert_eq!(err.kind(), ComparatorErrorKind::Antisymmetry);
+    assert_eq!(v, [1, 2, 3, 3]);
+}
+
+#[test]
+fn test_checked_sort_transitivity() {
+    // Rock, paper, scissors: antisymmetric, but every element beats another.
+    let compare = |a: &u32, b: &u32| match (a % 3 + 3 - b % 3) % 3 {
+        0 => Equal,
+        1 => Greater,
+        _ => Less,
+    };
+    let mut rng = weak_rng();
+    for &len in &[3, 10, 100, 1000] {
+        let mut v: Vec<u32> = (0..len).collect();
+        rng.shuffle(&mut v[..]);
+        let original = v.clone();
+        let err = checked_sort_by(&mut v, &compare).unwrap_err();
+        assert_eq!(v, original);
+        assert_eq!(err.kind(), ComparatorErrorKind::Transitivity);
+        assert_eq!(err.comparisons().len(), 3);
+        let c = err.comparisons();
+        assert!(c[0].2 != Greater && c[1].2 != Greater && c[2].2 == Greater);
+        assert_eq!((c[0].0, c[0].1, c[1].1), (c[2].0, c[1].0, c[2].1));
+        check_comparisons(&original, &err, &compare);
+    }
+}
+
+#[test]
+fn test_checked_sort_display() {
+    let mut v = [2, 1];
+    let err = checked_sort_by(&mut v, &|_, _| Less).unwrap_err();
+    let (i, j, _) = err.comparisons()[0];
+    assert_eq!(err.to_string(),
+               format!("comparison function is not antisymmetric: v[{}] vs v[{}] is Less, v[{}] vs v[{}] is Less",
+                       i, j, j, i));
+}
+
+#[test]
+fn test_checked_sort_erratic() {
+    // Comparisons that are random enough to break the checks of the
+    // "assert_working_compare" feature, which must not panic here.
+    let state = std::cell::Cell::new(1u32);
+    let compare = |_: &u32, _: &u32| {
+        let x = state.get().wrapping_mul(1_103_515_245).wrapping_add(12345);
+        state.set(x);
+        [Less, Equal, Greater][(x >> 16) as usize % 3]
+    };
+    for &len in &[5, 50, 5000] {
+        let mut v: Vec<u32> = (0..len).collect();
+        weak_rng().shuffle(&mut v[..]);
+        let original = v.clone();
+        assert!(checked_sort_by(&mut v, &compare).is_err());
+        assert_eq!(v, original);
+    }
+}
@@ -0,0 +1,137 @@
+#![cfg(feature = "alloc")]
+#![allow(clippy::float_cmp)]
+
+extern crate quickersort;
+extern crate rand;
+
+use std::f64;
+use quickersort::{sort_points_morton, sort_points_morton_with, sort_points_hilbert, sort_points_hilbert_with};
+use quickersort::BoundingBox;
+use rand::{Rng, weak_rng};
+
+// Every point of a grid `side` wide, shuffled.
+fn grid_2d(side: u32) -> Vec<[f64; 2]> {
+    let mut v: Vec<_> = (0..side * side).map(|i| [(i % side) as f64, (i / side) as f64]).collect();
+    weak_rng().shuffle(&mut v[..]);
+    v
+}
+
+fn grid_3d(side: u32) -> Vec<[f64; 3]> {
+    let mut v: Vec<_> = (0..side * side * side)
+        .map(|i| [(i % side) as f64, (i / side % side) as f64, (i / side / side) as f64]).collect();
+    weak_rng().shuffle(&mut v[..]);
+    v
+}
+
+// The Z-order key of small whole coordinates, with the first lowest.
+fn morton_key(p: &[f64]) -> u64 {
+    let mut key = 0;
+    for bit in 0..16 {
+        for (i, &x) in p.iter().enumerate() {
+            key |= ((x as u64 >> bit) & 1) << (bit * p.len() + i);
+        }
+    }
+    key
+}
+
+fn is_adjacent(a: &[f64], b: &[f64]) -> bool {
+    a.iter().zip(b).map(|(x, y)| (x - y).abs()).sum::<f64>() == 1.0
+}
+
+#[test]
+fn test_sort_points_morton() {
+    for &side in &[16, 64] {
+        let mut v = grid_2d(side);
+        let mut expected = v.clone();
+        expected.sort_by_key(|p| morton_key(p));
+        sort_points_morton_with(&mut v, &BoundingBox::new([0.0, 0.0], [side as f64; 2]));
+        assert_eq!(v, expected);
+
+        let mut v: Vec<[f32; 2]> = grid_2d(side).iter().map(|p| [p[0] as f32, p[1] as f32]).collect();
+        sort_points_morton_with(&mut v, &BoundingBox::new([0.0, 0.0], [side as f32; 2]));
+        assert!(v.iter().zip(&expected).all(|(a, b)| a[0] as f64 == b[0] && a[1] as f64 == b[1]));
+    }
+    for &side in &[8, 16] {
+        let mut v = grid_3d(side);
+        let mut expected = v.clone();
+        expected.sort_by_key(|p| morton_key(p));
+        sort_points_morton_with(&mut v, &BoundingBox::new([0.0; 3], [side as f64; 3]));
+        assert_eq!(v, expected);
+    }
+}
+
+#[test]
+fn test_sort_points_hilbert() {
+    // Every step along a Hilbert curve goes to a neighbouring cell.
+    for &side in &[16, 64] {
+        let mut v = grid_2d(side);
+        sort_points_hilbert_with(&mut v, &BoundingBox::new([0.0, 0.0], [side as f64; 2]));
+        assert_eq!(v[0], [0.0, 0.0]);
+        assert!(v.windows(2).all(|w| is_adjacent(&w[0], &w[1])));
+    }
+    for &side in &[8, 16] {
+        let mut v = grid_3d(side);
+        sort_points_hilbert_with(&mut v, &BoundingBox::new([0.0; 3], [side as f64; 3]));
+        assert_eq!(v[0], [0.0; 3]);
+        assert!(v.windows(2).all(|w| is_adjacent(&w[0], &w[1])));
+    }
+}
+
+#[test]
+fn test_sort_points_special_coordinates() {
+    let nan = f64::NAN;
+    let inf = f64::INFINITY;
+    let v = vec![[nan, 0.0], [1.0, 1.0], [-0.0, 0.0], [0.0, nan], [inf, inf], [0.0, -0.0], [-inf, -inf],
+                 [0.5, 0.5]];
+    // Points with a NaN go last in their old order, infinities go to the
+    // corners of the box, and `-0` and `+0` are in the same cell.
+    let mut w = v.clone();
+    sort_points_morton(&mut w);
+    let expected: Vec<_> = [2, 5, 6, 7, 1, 4, 0, 3].iter().map(|&i| v[i]).collect();
+    assert!(w.iter().zip(&expected).all(|(a, b)| a[0].to_bits() == b[0].to_bits() && a[1].to_bits() == b[1].to_bits()));
+
+    let mut w = v.clone();
+    sort_points_hilbert(&mut w);
+    assert!(w[6][0].is_nan() && w[7][1].is_nan());
+    assert!(w[..2].iter().all(|p| p[0] == 0.0));
+    assert!(w[0][0].is_sign_negative() && w[1][1].is_sign_negative());
+}
+
+#[test]
+fn test_sort_points_same_box() {
+    // Sorting part of the points in the same box keeps their order.
+    let mut rng = weak_rng();
+    let bounds = BoundingBox::new([-1.0, -1.0, -1.0], [1.0, 1.0, 1.0]);
+    let mut v: Vec<[f64; 3]> = (0..5000).map(|_| [rng.gen_range(-1.5, 1.5), rng.gen(), rng.gen()]).collect();
+    let mut part: Vec<_> = v.iter().cloned().filter(|p| p[1] < 0.3).collect();
+    sort_points_hilbert_with(&mut v, &bounds);
+    sort_points_hilbert_with(&mut part, &bounds);
+    let expected: Vec<_> = v.iter().cloned().filter(|p| p[1] < 0.3).collect();
+    assert_eq!(part, expected);
+}
+
+#[test]
+fn test_bounding_box_of() {
+    // The box leaves out infinities, which then go to its sides.
+    let mut v = [[2.0, 0.0], [f64::INFINITY, 0.0], [1.0, 0.0], [0.0, 0.0]];
+    sort_points_morton(&mut v);
+    assert_eq!(v, [[0.0, 0.0], [1.0, 0.0], [2.0, 0.0], [f64::INFINITY, 0.0]]);
+
+    let mut v = [[f64::NAN, 1.0], [f64::NEG_INFINITY, 0.0]];
+    sort_points_hilbert(&mut v);
+    assert!(v[0][0] == f64::NEG_INFINITY && v[1][0].is_nan());
+    sort_points_hilbert::<[f64; 3]>(&mut []);
+    sort_points_morton(&mut [[1.0f32, 2.0]]);
+}
+
+#[test]
+#[should_panic]
+fn test_bounding_box_nan() {
+    BoundingBox::new([0.0, f64::NAN], [1.0, 1.0]);
+}
+
+#[test]
+#[should_panic]
+fn test_bounding_box_inverted() {
+    BoundingBox::new([0.0, 2.0], [1.0, 1.0]);
+}
@@ -0,0 +1,85 @@
+#![allow(clippy::float_cmp, clippy::single_range_in_vec_init)]
+
+extern crate quickersort;
+extern crate rand;
+
+use std::f64;
+use std::ops::Range;
+use quickersort::{cluster_floats, dedup_floats_with_tolerance, sort_floats, ToleranceMode, F16};
+use rand::{Rng, weak_rng};
+
+fn clusters<T: quickersort::Float + Into<f64>>(v: &[T], eps: f64, mode: ToleranceMode) -> Vec<Range<usize>> {
+    cluster_floats(v, eps, mode).collect()
+}
+
+#[test]
+fn test_cluster_floats_absolute() {
+    let v = [-1.0, -0.95, -0.8, -0.0, 0.0, 0.05, 1.0, 1.0, f64::INFINITY, f64::INFINITY, f64::NAN, f64::NAN];
+    assert_eq!(clusters(&v, 0.1, ToleranceMode::Absolute), [0..2, 2..3, 3..6, 6..8, 8..10, 10..12]);
+    // Equal numbers, including `-0` and `+0`, stay together at zero
+    // tolerance.
+    assert_eq!(clusters(&v, 0.0, ToleranceMode::Absolute), [0..1, 1..2, 2..3, 3..5, 5..6, 6..8, 8..10, 10..12]);
+    // A NaN is never part of a cluster of numbers.
+    assert_eq!(clusters(&v, f64::INFINITY, ToleranceMode::Absolute), [0..10, 10..12]);
+    assert_eq!(clusters(&[0.0f64; 0], 1.0, ToleranceMode::Absolute), []);
+    // Clusters are measured from their first number, not chained.
+    let v = [0.0, 0.6, 1.2, 1.8];
+    assert_eq!(clusters(&v, 1.0, ToleranceMode::Absolute), [0..2, 2..4]);
+}
+
+#[test]
+fn test_cluster_floats_ulps() {
+    let one = 1.0f32;
+    let up = |x: f32, n: i32| f32::from_bits((x.to_bits() as i32 + n) as u32);
+    let v = [up(one, -2), one, up(one, 2), up(one, 3), up(one, 5)];
+    assert_eq!(clusters(&v, 2.0, ToleranceMode::Ulps), [0..2, 2..4, 4..5]);
+    assert_eq!(clusters(&v, 4.0, ToleranceMode::Ulps), [0..3, 3..5]);
+
+    // The smallest negative and positive numbers are two units apart, with
+    // `-0` and `+0` counting as one.
+    let tiny = f32::from_bits(1);
+    let v = [-tiny, -0.0, 0.0, tiny];
+    assert_eq!(clusters(&v, 0.0, ToleranceMode::Ulps), [0..1, 1..3, 3..4]);
+    assert_eq!(clusters(&v, 1.0, ToleranceMode::Ulps), [0..3, 3..4]);
+    assert_eq!(clusters(&v, 2.0, ToleranceMode::Ulps), [0..4]);
+
+    let v = [f64::MAX, f64::INFINITY];
+    assert_eq!(clusters(&v, 1.0, ToleranceMode::Ulps), [0..2]);
+
+    let v: Vec<F16> = [1.0, 1.0009766, 1.0019531, 1.0029297].iter().map(|&x| F16::from_f32(x)).collect();
+    assert_eq!(clusters(&v, 1.0, ToleranceMode::Ulps), [0..2, 2..4]);
+}
+
+#[test]
+fn test_dedup_floats_with_tolerance() {
+    let mut rng = weak_rng();
+    for &mode in &[ToleranceMode::Absolute, ToleranceMode::Ulps] {
+        let eps = if mode == ToleranceMode::Absolute { 0.01 } else { 1e13 };
+        let mut v: Vec<f64> = rng.gen_iter::<f64>().take(1000).collect();
+        v.extend_from_slice(&[f64::NAN, -f64::NAN, 0.0, -0.0, 0.0, f64::INFINITY]);
+        rng.shuffle(&mut v[..]);
+        sort_floats(&mut v[..]);
+
+        let expected: Vec<f64> = cluster_floats(&v, eps, mode).map(|r| v[r.start]).collect();
+        let len = dedup_floats_with_tolerance(&mut v[..], eps, mode);
+        assert_eq!(len, expected.len());
+        assert!(v[..len].iter().zip(&expected).all(|(a, b)| a.to_bits() == b.to_bits()));
+        assert!(v[len - 1].is_nan() && !v[len - 2].is_nan());
+        assert!(v[..len].iter().any(|x| x.to_bits() == (-0.0f64).to_bits()));
+        assert!(!v[..len].iter().any(|x| x.to_bits() == 0.0f64.to_bits()));
+        // Every pair of numbers left is further apart than the tolerance.
+        assert_eq!(cluster_floats(&v[..len], eps, mode).count(), len);
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_cluster_floats_negative_tolerance() {
+    cluster_floats(&[1.0f64], -1.0, ToleranceMode::Absolute);
+}
+
+#[test]
+#[should_panic]
+fn test_dedup_floats_nan_tolerance() {
+    dedup_floats_with_tolerance(&mut [1.0f64], f64::NAN, ToleranceMode::Ulps);
+}
@@ -1,4 +1,4 @@
-#![cfg_attr(feature = "cargo-clippy", allow(float_cmp))]
+#![allow(clippy::float_cmp, clippy::neg_multiply, clippy::same_item_push)]
 
 extern crate quickersort;
 extern crate rand;
@@ -9,11 +9,11 @@ mod bench {
 
 use std::f64;
 use num_traits::{ToPrimitive};
-use quickersort::{sort_floats};
+use std::cmp::Ordering;
+use quickersort::{sort_floats, sort_floats_with, FloatOrder, Direction, NanPlacement};
 use rand::{Rng, weak_rng};
 
-#[test]
-fn test_sort_floats() {
+fn sorted_specials() -> Vec<f64> {
     let sorted_negs: Vec<_> = (0..10u32).map(|i| -1.0f64 * (11 - i).to_f64().unwrap()).collect();
     let sorted_pos: Vec<_> = (0..10u32).map(|i| (i + 1).to_f64().unwrap()).collect();
     let mut sorted = vec![f64::NEG_INFINITY];
@@ -25,6 +25,12 @@ fn test_sort_floats() {
     for _ in 0..10 {
         sorted.push(f64::NAN);
     }
+    sorted
+}
+
+#[test]
+fn test_sort_floats() {
+    let sorted = sorted_specials();
 
     let mut rng = weak_rng();
     for _ in 0..1000 {
@@ -40,6 +46,223 @@ fn test_sort_floats() {
     sort_floats(&mut xs[..]);
 }
 
+#[cfg(feature = "alloc")]
+#[test]
+fn test_radix_sort_floats() {
+    let mut rng = weak_rng();
+    for &len in &[0usize, 1, 20, 5000, 100_000] {
+        let mut v = rng.gen_iter::<f64>().map(|x| 1e6 * (x - 0.5)).take(len).collect::<Vec<_>>();
+        for _ in 0..len / 100 {
+            v.extend_from_slice(&[f64::NAN, -f64::NAN, 0.0, -0.0, f64::INFINITY, f64::NEG_INFINITY]);
+        }
+        rng.shuffle(&mut v[..]);
+        let mut expected = v.clone();
+        sort_floats(&mut expected[..]);
+        quickersort::radix_sort_floats(&mut v[..]);
+        assert_float_eq(&v[..], &expected[..]);
+
+        let mut v = v.iter().map(|&x| x as f32).collect::<Vec<_>>();
+        rng.shuffle(&mut v[..]);
+        quickersort::radix_sort_floats(&mut v[..]);
+        let v = v.iter().map(|&x| x as f64).collect::<Vec<_>>();
+        assert_floats_sorted(&v[..]);
+        assert_eq!(v.len(), expected.len());
+    }
+
+    let mut w = sorted_specials();
+    w.extend(vec![0.0; 2000]);
+    w.extend(vec![-0.0; 2000]);
+    rng.shuffle(&mut w[..]);
+    quickersort::radix_sort_floats(&mut w[..]);
+    assert_floats_sorted(&w[..]);
+}
+
+// The order `sort_floats_with` should sort into, defined with slow
+// comparisons.
+fn expected_cmp(order: FloatOrder, a: f64, b: f64) -> Ordering {
+    let ord = match order.nans {
+        NanPlacement::BySign if order.signed_zeros => a.total_cmp(&b),
+        NanPlacement::BySign if a.is_nan() || b.is_nan() => a.total_cmp(&b),
+        _ if a.is_nan() || b.is_nan() => {
+            let ord = a.is_nan().cmp(&b.is_nan());
+            return if order.nans == NanPlacement::First { ord.reverse() } else { ord };
+        }
+        _ if order.signed_zeros => a.total_cmp(&b),
+        _ => a.partial_cmp(&b).unwrap(),
+    };
+    if order.direction == Direction::Descending { ord.reverse() } else { ord }
+}
+
+#[test]
+fn test_sort_floats_with() {
+    let mut rng = weak_rng();
+    let payload_nan = |bits: u64| f64::from_bits(0x7ff8_0000_0000_0000 | bits);
+    let mut v = sorted_specials();
+    v.extend(vec![0.0, -0.0, 1.0, -1.0, -0.0]);
+    v.extend((1..20).map(|i| payload_nan(i * 7919 % 101)));
+    v.extend((1..20).map(|i| -payload_nan(i * 7919 % 97)));
+    v.extend(rng.gen_iter::<f64>().map(|x| 100.0 * (x - 0.5)).take(200));
+
+    let mut all = v.clone();
+    all.sort_by(|a, b| a.total_cmp(b));
+    for &direction in &[Direction::Ascending, Direction::Descending] {
+        for &nans in &[NanPlacement::First, NanPlacement::Last, NanPlacement::BySign] {
+            for &signed_zeros in &[false, true] {
+                let order = FloatOrder { direction, nans, signed_zeros };
+                for _ in 0..20 {
+                    rng.shuffle(&mut v[..]);
+                    let mut w = v.clone();
+                    sort_floats_with(&mut w[..], order);
+                    for pair in w.windows(2) {
+                        assert!(expected_cmp(order, pair[0], pair[1]) != Ordering::Greater,
+                                "{:?}: {:?} before {:?}", order, pair[0], pair[1]);
+                    }
+                    w.sort_by(|a, b| a.total_cmp(b));
+                    assert!(w.iter().zip(&all).all(|(a, b)| a.to_bits() == b.to_bits()));
+                }
+            }
+        }
+    }
+
+    let mut w = v.clone();
+    sort_floats_with(&mut w[..], FloatOrder::TOTAL_ORDER);
+    assert!(w.iter().zip(&all).all(|(a, b)| a.to_bits() == b.to_bits()));
+}
+
+// The order `sort_floats` sorts into, where all NaNs are equal.
+fn sort_floats_cmp(a: &f64, b: &f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (false, false) => a.total_cmp(b),
+        (x, y) => x.cmp(&y),
+    }
+}
+
+fn records() -> Vec<(f64, usize)> {
+    let mut rng = weak_rng();
+    let mut keys = sorted_specials();
+    keys.extend(vec![0.0, -0.0, 1.0, -1.0, -0.0, -f64::NAN]);
+    keys.extend(rng.gen_iter::<u8>().map(|x| (x % 8) as f64 - 4.0).take(300));
+    rng.shuffle(&mut keys[..]);
+    keys.into_iter().enumerate().map(|(i, k)| (k, i)).collect()
+}
+
+#[test]
+fn test_sort_by_float_key() {
+    let v = records();
+    let mut expected = v.clone();
+    expected.sort_by(|a, b| sort_floats_cmp(&a.0, &b.0));
+
+    let mut w = v.clone();
+    quickersort::sort_by_float_key(&mut w[..], |x| x.0);
+    // Not stable, so only the keys are in the same order.
+    assert!(w.iter().zip(&expected).all(|(a, b)| {
+        a.0.to_bits() == b.0.to_bits() || (a.0.is_nan() && b.0.is_nan())
+    }));
+    w.sort_by_key(|x| x.1);
+    assert!(w.iter().zip(&v).all(|(a, b)| a.1 == b.1 && a.0.to_bits() == b.0.to_bits()));
+}
+
+#[test]
+#[cfg(not(feature="assert_working_compare"))]
+fn test_sort_by_float_key_erratic() {
+    // A key that does not always return the same thing must not break
+    // anything.
+    let v = records();
+    let calls = std::cell::Cell::new(0u32);
+    let mut w = v.clone();
+    quickersort::sort_by_float_key(&mut w[..], |x| {
+        calls.set(calls.get() + 1);
+        if calls.get() % 7 == 3 { f64::NAN } else { x.0 }
+    });
+    w.sort_by_key(|x| x.1);
+    assert!(w.iter().zip(&v).all(|(a, b)| a.1 == b.1));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_stable_sort_by_float_key() {
+    let v = records();
+    let mut expected = v.clone();
+    expected.sort_by(|a, b| sort_floats_cmp(&a.0, &b.0));
+
+    let mut w = v.clone();
+    quickersort::stable_sort_by_float_key(&mut w[..], |x| x.0);
+    assert!(w.iter().zip(&expected).all(|(a, b)| a.1 == b.1));
+}
+
+#[test]
+fn test_argsort_floats() {
+    let keys: Vec<f64> = records().into_iter().map(|x| x.0).collect();
+    let mut expected: Vec<usize> = (0..keys.len()).collect();
+    expected.sort_by(|&a, &b| sort_floats_cmp(&keys[a], &keys[b]));
+
+    let mut indices = vec![0; keys.len()];
+    quickersort::argsort_floats_into(&keys[..], &mut indices[..]);
+    assert_eq!(indices, expected);
+    #[cfg(feature = "alloc")]
+    assert_eq!(quickersort::argsort_floats(&keys[..]), expected);
+
+    let mut empty: [usize; 0] = [];
+    quickersort::argsort_floats_into(&[0.0f32; 0], &mut empty);
+}
+
+#[test]
+#[should_panic]
+fn test_argsort_floats_into_wrong_length() {
+    quickersort::argsort_floats_into(&[1.0, 2.0], &mut [0; 3]);
+}
+
+#[test]
+fn test_total_floats() {
+    use quickersort::{Total, TotalF32, TotalF64};
+    use std::collections::{BTreeSet, HashSet};
+
+    let mut rng = weak_rng();
+    for _ in 0..100 {
+        let mut v = sorted_specials();
+        v.push(-f64::NAN);
+        rng.shuffle(&mut v[..]);
+        let mut expected = v.clone();
+        sort_floats(&mut expected[..]);
+        quickersort::sort(TotalF64::from_mut_slice(&mut v[..]));
+        assert_float_eq(&v[..], &expected[..]);
+        for pair in Total::from_slice(&v[..]).windows(2) {
+            assert!(pair[0] <= pair[1]);
+        }
+    }
+
+    let mut v = sorted_specials();
+    v.push(-f64::NAN);
+    let hashed = v.iter().map(|&x| Total(x)).collect::<HashSet<_>>();
+    let ordered = v.iter().map(|&x| Total(x)).collect::<BTreeSet<_>>();
+    // All NaNs are one key, but -0 and +0 are two.
+    assert_eq!(hashed.len(), sorted_specials().len() - 9);
+    assert_eq!(ordered.len(), hashed.len());
+    assert!(Total::as_floats(&ordered.into_iter().collect::<Vec<_>>()).iter().zip(&v).all(|(a, b)|
+        a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan())));
+
+    assert!(TotalF32::from(-0.0) < TotalF32::from(0.0));
+    assert!(Total(f32::NAN) == Total(-f32::NAN));
+    assert!(Total(f32::NAN) > Total(f32::INFINITY));
+    let mut w = [Total(1.0f32), Total(f32::NAN), Total(-2.0f32)];
+    w.sort();
+    Total::as_mut_floats(&mut w[..])[0] = 5.0;
+    assert_eq!(w[0], Total(5.0f32));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_radix_sort_total_floats() {
+    let mut rng = weak_rng();
+    let mut v = sorted_specials();
+    v.extend(rng.gen_iter::<f64>().map(|x| 100.0 * (x - 0.5)).take(5000));
+    rng.shuffle(&mut v[..]);
+    let mut expected = v.clone();
+    sort_floats(&mut expected[..]);
+    quickersort::radix_sort(quickersort::TotalF64::from_mut_slice(&mut v[..]));
+    assert_float_eq(&v[..], &expected[..]);
+}
+
 #[test]
 fn test_sort_random_floats() {
     let mut rng = weak_rng();
@@ -93,4 +316,43 @@ fn assert_floats_sorted(v: &[f64]) {
     assert!(nans.iter().all(|x| x.is_nan()));
 }
 
+// A `Float` whose `is_nan` disagrees with its `PartialOrd`.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+struct NanBlind(f64);
+
+impl quickersort::Float for NanBlind {
+    fn one() -> NanBlind { NanBlind(1.0) }
+    fn zero() -> NanBlind { NanBlind(0.0) }
+    fn neg_zero() -> NanBlind { NanBlind(-0.0) }
+    fn is_nan(self) -> bool { false }
+    fn is_sign_negative(self) -> bool { self.0.is_sign_negative() }
+
+    type Bits = u64;
+
+    fn order_key(self) -> u64 {
+        quickersort::Float::order_key(self.0)
+    }
+}
+
+#[test]
+fn test_sort_floats_inconsistent_impl() {
+    let mut rng = weak_rng();
+    let v: Vec<NanBlind> = (0..500).map(|i| NanBlind(if i % 5 == 0 { f64::NAN } else { rng.gen() })).collect();
+    let bits = |w: &[NanBlind]| {
+        let mut bits: Vec<u64> = w.iter().map(|x| x.0.to_bits()).collect();
+        bits.sort();
+        bits
+    };
+
+    for &order in &[FloatOrder::default(), FloatOrder { direction: Direction::Descending, ..FloatOrder::default() }] {
+        let mut w = v.clone();
+        sort_floats_with(&mut w, order);
+        assert_eq!(bits(&w), bits(&v));
+    }
+    let mut indices = vec![0; v.len()];
+    quickersort::argsort_floats_into(&v, &mut indices);
+    indices.sort();
+    assert!(indices.iter().enumerate().all(|(i, &j)| i == j));
+}
+
 }
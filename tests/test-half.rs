@@ -0,0 +1,98 @@
+extern crate quickersort;
+extern crate rand;
+
+use quickersort::{Bf16, F16, Float};
+use rand::{Rng, weak_rng};
+
+#[test]
+fn test_half_round_trip() {
+    for bits in 0..=u16::MAX {
+        let h = F16::from_bits(bits);
+        let b = Bf16::from_bits(bits);
+        assert_eq!(h.is_infinite(), h.to_f32().is_infinite());
+        assert_eq!(b.is_infinite(), b.to_f32().is_infinite());
+        if h.is_nan() {
+            assert!(h.to_f32().is_nan());
+            assert!(F16::from_f32(h.to_f32()).is_nan());
+        } else {
+            assert_eq!(F16::from_f32(h.to_f32()).to_bits(), bits);
+        }
+        if b.is_nan() {
+            assert!(b.to_f32().is_nan());
+            assert!(Bf16::from_f32(b.to_f32()).is_nan());
+        } else {
+            assert_eq!(Bf16::from_f32(b.to_f32()).to_bits(), bits);
+        }
+    }
+    assert_eq!(F16::from_bits(0x3c00).to_f32(), 1.0);
+    assert_eq!(F16::from_bits(0x0001).to_f32(), 2f32.powi(-24));
+    assert_eq!(F16::from_bits(0x7bff).to_f32(), 65504.0);
+    assert_eq!(Bf16::from_bits(0x3f80).to_f32(), 1.0);
+}
+
+// Checks that `to_f32(from_f32(x))` is the nearest value to `x`, with ties
+// going to an even mantissa.
+fn check_rounding<H: Copy, F: Fn(f32) -> H, T: Fn(H) -> f32, B: Fn(H) -> u16>(x: f32, from: F, to: T, bits: B,
+                                                                               from_bits: &dyn Fn(u16) -> H) {
+    let h = from(x);
+    let b = bits(h);
+    let y = to(h) as f64;
+    if y.is_infinite() {
+        // Only values at least halfway to the next power of two overflow.
+        let max = to(from_bits(b - 1)) as f64;
+        assert!((x as f64).abs() >= max, "{} rounded to {}", x, y);
+        return;
+    }
+    let err = (y - x as f64).abs();
+    for &n in &[b.wrapping_sub(1), b.wrapping_add(1)] {
+        if n & 0x7fff == 0x7fff || (b & 0x7fff == 0 && n & 0x7fff != 1) {
+            continue;
+        }
+        let other = (to(from_bits(n)) as f64 - x as f64).abs();
+        assert!(err < other || (err == other && b & 1 == 0), "{} rounded to {}", x, y);
+    }
+}
+
+#[test]
+fn test_half_rounding() {
+    let mut rng = weak_rng();
+    for _ in 0..100_000 {
+        let x = f32::from_bits(rng.gen::<u32>());
+        if x.is_nan() {
+            assert!(F16::from_f32(x).is_nan() && Bf16::from_f32(x).is_nan());
+            continue;
+        }
+        check_rounding(x, Bf16::from_f32, Bf16::to_f32, Bf16::to_bits, &Bf16::from_bits);
+        // Also pick values in the range of `F16`, including subnormals.
+        let x = (rng.gen::<f32>() - 0.5) * 2f32.powi(rng.gen_range(-30, 17));
+        check_rounding(x, F16::from_f32, F16::to_f32, F16::to_bits, &F16::from_bits);
+    }
+    // Ties go to even.
+    assert_eq!(F16::from_f32(1.0 + 2f32.powi(-11)).to_bits(), 0x3c00);
+    assert_eq!(F16::from_f32(1.0 + 3.0 * 2f32.powi(-11)).to_bits(), 0x3c02);
+    assert_eq!(F16::from_f32(65520.0).to_f32(), f32::INFINITY);
+    assert_eq!(F16::from_f32(2f32.powi(-25)).to_bits(), 0);
+    assert_eq!(F16::from_f32(2f32.powi(-25) * 1.5).to_bits(), 1);
+}
+
+#[test]
+fn test_sort_half_floats() {
+    let mut rng = weak_rng();
+    for &len in &[0usize, 10, 1000] {
+        let mut v = (0..len).map(|_| F16::from_bits(rng.gen())).collect::<Vec<_>>();
+        v.extend_from_slice(&[F16::zero(), F16::neg_zero(), F16::one()]);
+        let mut expected = v.iter().map(|x| x.to_f32()).collect::<Vec<_>>();
+        quickersort::sort_floats(&mut expected[..]);
+        quickersort::sort_floats(&mut v[..]);
+        assert!(v.iter().zip(&expected).all(|(a, b)|
+            a.to_f32().to_bits() == b.to_bits() || (a.is_nan() && b.is_nan())));
+
+        let mut v = (0..len).map(|_| Bf16::from_bits(rng.gen())).collect::<Vec<_>>();
+        v.extend_from_slice(&[Bf16::zero(), Bf16::neg_zero(), Bf16::one()]);
+        let mut expected = v.iter().map(|x| x.to_f32()).collect::<Vec<_>>();
+        quickersort::sort_floats(&mut expected[..]);
+        quickersort::sort_floats(&mut v[..]);
+        assert!(v.iter().zip(&expected).all(|(a, b)|
+            a.to_f32().to_bits() == b.to_bits() || (a.is_nan() && b.is_nan())));
+    }
+}
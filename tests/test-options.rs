@@ -0,0 +1,67 @@
+extern crate quickersort;
+extern crate rand;
+
+use quickersort::{sort_options, sort_options_by, sort_options_by_key, NullsPlacement};
+use rand::{Rng, weak_rng};
+
+fn options(len: usize) -> Vec<Option<i64>> {
+    let mut rng = weak_rng();
+    (0..len).map(|_| if rng.gen_weighted_bool(4) { None } else { Some(rng.gen_range(-100, 100)) }).collect()
+}
+
+#[test]
+fn test_sort_options() {
+    for &len in &[0, 1, 2, 10, 100, 5000] {
+        let v = options(len);
+        let nones = v.iter().filter(|x| x.is_none()).count();
+
+        // `Option`'s own order puts `None` first.
+        let mut expected = v.clone();
+        expected.sort();
+        let mut w = v.clone();
+        sort_options(&mut w, NullsPlacement::First);
+        assert_eq!(w, expected);
+
+        expected.rotate_left(nones);
+        let mut w = v.clone();
+        sort_options(&mut w, NullsPlacement::Last);
+        assert_eq!(w, expected);
+    }
+}
+
+#[test]
+fn test_sort_options_by() {
+    let v = options(1000);
+    let mut expected: Vec<_> = v.iter().cloned().filter(|x| x.is_some()).collect();
+    expected.sort_by(|a, b| b.cmp(a));
+    let nones = v.len() - expected.len();
+
+    let mut w = v.clone();
+    sort_options_by(&mut w, NullsPlacement::Last, &|a, b| b.cmp(a));
+    assert_eq!(w[..expected.len()], expected[..]);
+    assert!(w[expected.len()..].iter().all(|x| x.is_none()));
+
+    let mut w = v.clone();
+    sort_options_by(&mut w, NullsPlacement::First, &|a, b| b.cmp(a));
+    assert!(w[..nones].iter().all(|x| x.is_none()));
+    assert_eq!(w[nones..], expected[..]);
+}
+
+#[test]
+fn test_sort_options_by_key() {
+    let mut rng = weak_rng();
+    let v: Vec<Option<String>> = options(1000).into_iter().map(|x| x.map(|x| x.to_string())).collect();
+    let mut w = v.clone();
+    sort_options_by_key(&mut w, NullsPlacement::Last, |s| s.parse::<i64>().unwrap());
+    let parsed: Vec<_> = w.iter().map(|x| x.as_ref().map(|s| s.parse::<i64>().unwrap())).collect();
+    let mut expected: Vec<_> = v.iter().map(|x| x.as_ref().map(|s| s.parse::<i64>().unwrap())).collect();
+    expected.sort();
+    let nones = expected.iter().filter(|x| x.is_none()).count();
+    expected.rotate_left(nones);
+    assert_eq!(parsed, expected);
+
+    let mut w: Vec<Option<u8>> = vec![None; 100];
+    rng.shuffle(&mut w[..]);
+    sort_options_by_key(&mut w, NullsPlacement::First, |&x| x);
+    assert!(w.iter().all(|x| x.is_none()));
+}
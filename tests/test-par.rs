@@ -0,0 +1,164 @@
+#![cfg(feature = "parallel")]
+
+extern crate quickersort;
+extern crate rand;
+
+use quickersort::{par_sort_by, par_sort_by_with_min_len, par_sort_by_with_threads, par_stable_sort_by,
+                  par_stable_sort_by_with_min_len, par_stable_sort_by_with_threads, stable_sort_by};
+use rand::{Rng, weak_rng};
+use std::cmp::min;
+
+#[derive(Clone, Copy)]
+enum Pattern { Sawtooth, Rand, Stagger, Plateau, Shuffle }
+
+#[derive(Clone, Copy)]
+enum Variant { Ident, Reverse, ReverseFront, ReverseBack, Sorted, Dither }
+
+// The same inputs that examples/perf_txt.rs measures.
+fn generate_int(pattern: Pattern, variant: Variant, size: usize, m: usize) -> Vec<i32> {
+    let mut rng = weak_rng();
+    let mut rng_it = rng.gen_iter::<usize>();
+    let mut random = || rng_it.next().unwrap();
+    let mut ret_val = Vec::with_capacity(size);
+    let (mut j, mut k) = (0, 0);
+    for i in 0 .. size {
+        ret_val.push(match pattern {
+            Pattern::Sawtooth => i % m,
+            Pattern::Rand => random(),
+            Pattern::Stagger => (i*m + i) % size,
+            Pattern::Plateau => min(i, m),
+            Pattern::Shuffle => if random() % m == 0 { j+=2; j } else { k += 2; k },
+        } as i32);
+    }
+    match variant {
+        Variant::Ident => (),
+        Variant::Reverse => ret_val.reverse(),
+        Variant::ReverseFront => ret_val[0 .. size / 2].reverse(),
+        Variant::ReverseBack => ret_val[size / 2 .. ].reverse(),
+        Variant::Sorted => quickersort::sort(&mut ret_val),
+        Variant::Dither => for x in &mut ret_val { let k = *x % 5; *x = k },
+    }
+    ret_val
+}
+
+#[test]
+fn test_par_sort() {
+    let mut rng = weak_rng();
+    for &len in &[0usize, 1, 100, 20_000, 200_000] {
+        for &modulus in &[2usize, 1000, usize::MAX] {
+            let v = rng.gen_iter::<usize>().take(len).map(|x| x % modulus).collect::<Vec<_>>();
+            let mut sorted = v.clone();
+            sorted.sort();
+            let mut reversed = sorted.clone();
+            reversed.reverse();
+            for pattern in [v, sorted.clone(), reversed] {
+                let mut w = pattern.clone();
+                par_sort_by(&mut w[..], &|a, b| a.cmp(b));
+                assert_eq!(w, sorted);
+
+                let mut w = pattern.clone();
+                par_sort_by_with_min_len(&mut w[..], 64, &|a, b| a.cmp(b));
+                assert_eq!(w, sorted);
+
+                // Also on machines with a single thread.
+                let mut w = pattern;
+                par_sort_by_with_threads(&mut w[..], 64, 4, &|a, b| a.cmp(b));
+                assert_eq!(w, sorted);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_par_sort_small_min_len() {
+    // Partitions too small to partition again must still be insertion
+    // sorted, like in `sort`.
+    let mut rng = weak_rng();
+    for &min_len in &[0, 1, 5] {
+        for &len in &[2usize, 10, 100, 1000] {
+            let v = rng.gen_iter::<u32>().take(len).collect::<Vec<_>>();
+            let mut sorted = v.clone();
+            sorted.sort();
+            let mut w = v.clone();
+            par_sort_by_with_threads(&mut w, min_len, 8, &|a, b| a.cmp(b));
+            assert_eq!(w, sorted);
+            let mut w = v;
+            par_sort_by_with_min_len(&mut w, min_len, &|a, b| a.cmp(b));
+            assert_eq!(w, sorted);
+        }
+    }
+}
+
+#[test]
+fn test_par_sort_broken_compare() {
+    let mut v = vec![0u8; 100_000];
+    par_sort_by_with_min_len(&mut v, 64, &|_, _| std::cmp::Ordering::Less);
+    par_sort_by_with_min_len(&mut v, 64, &|_, _| std::cmp::Ordering::Greater);
+    par_sort_by_with_threads(&mut v, 64, 4, &|_, _| std::cmp::Ordering::Less);
+    par_sort_by_with_threads(&mut v, 64, 4, &|_, _| std::cmp::Ordering::Greater);
+}
+
+#[test]
+fn test_par_stable_sort() {
+    for &size in &[0usize, 1, 1000, 100_000] {
+        for &m in &[2usize, 64, 4096] {
+            for &pattern in &[Pattern::Sawtooth, Pattern::Rand, Pattern::Stagger, Pattern::Plateau, Pattern::Shuffle] {
+                for &variant in &[Variant::Ident, Variant::Reverse, Variant::ReverseFront, Variant::ReverseBack,
+                                  Variant::Sorted, Variant::Dither] {
+                    // Tag each element with its position, and only compare the
+                    // values, so that the result shows whether the sort is stable.
+                    let v = generate_int(pattern, variant, size, m).into_iter().enumerate()
+                        .map(|(i, x)| (x % 1000, i)).collect::<Vec<_>>();
+                    let mut expected = v.clone();
+                    stable_sort_by(&mut expected, &|a, b| a.0.cmp(&b.0));
+
+                    let mut w = v.clone();
+                    par_stable_sort_by(&mut w, &|a, b| a.0.cmp(&b.0));
+                    assert_eq!(w, expected);
+
+                    let mut w = v.clone();
+                    par_stable_sort_by_with_min_len(&mut w, 100, &|a, b| a.0.cmp(&b.0));
+                    assert_eq!(w, expected);
+
+                    // Also on machines with a single thread.
+                    let mut w = v;
+                    par_stable_sort_by_with_threads(&mut w, 100, 4, &|a, b| a.0.cmp(&b.0));
+                    assert_eq!(w, expected);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_par_stable_sort_panic() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let v: Vec<i32> = generate_int(Pattern::Rand, Variant::Ident, 10_000, 0);
+    let mut sorted = v.clone();
+    sorted.sort();
+    let count = &AtomicUsize::new(0);
+    let compare = |limit: usize| move |a: &i32, b: &i32| {
+        if count.fetch_add(1, Ordering::Relaxed) == limit {
+            panic!("compare");
+        }
+        a.cmp(b)
+    };
+    let mut w = v.clone();
+    par_stable_sort_by_with_threads(&mut w, 100, 4, &compare(usize::MAX));
+    let total = count.swap(0, Ordering::Relaxed);
+
+    // Every element must still be in the slice exactly once after the
+    // comparison function panics, whether sorting a chunk or merging.
+    for &limit in &[total / 10, total * 9 / 10, total - 1] {
+        let mut w = v.clone();
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            par_stable_sort_by_with_threads(&mut w, 100, 4, &compare(limit));
+        }));
+        assert!(result.is_err());
+        count.store(0, Ordering::Relaxed);
+        w.sort();
+        assert_eq!(w, sorted);
+    }
+}
@@ -0,0 +1,124 @@
+extern crate quickersort;
+extern crate rand;
+
+use std::cmp::Ordering;
+use std::f64;
+use quickersort::{sort_partial, sort_partial_by, IncomparablePolicy};
+use rand::{Rng, weak_rng};
+
+fn floats() -> Vec<f64> {
+    let mut rng = weak_rng();
+    let mut v: Vec<f64> = rng.gen_iter::<u8>().map(|x| x as f64 - 128.0).take(500).collect();
+    v.extend_from_slice(&[f64::NAN, -f64::NAN, f64::INFINITY, f64::NEG_INFINITY, f64::NAN]);
+    rng.shuffle(&mut v[..]);
+    v
+}
+
+#[test]
+fn test_sort_partial_floats() {
+    let v = floats();
+    let mut expected: Vec<f64> = v.iter().cloned().filter(|x| !x.is_nan()).collect();
+    quickersort::sort_floats(&mut expected);
+
+    let mut w = v.clone();
+    assert_eq!(sort_partial(&mut w, IncomparablePolicy::Last), Ok(3));
+    assert_eq!(w[..w.len() - 3], expected[..]);
+    assert!(w[w.len() - 3..].iter().all(|x| x.is_nan()));
+
+    let mut w = v.clone();
+    assert_eq!(sort_partial(&mut w, IncomparablePolicy::First), Ok(3));
+    assert!(w[..3].iter().all(|x| x.is_nan()));
+    assert_eq!(w[3..], expected[..]);
+
+    let mut w = v.clone();
+    let err = sort_partial(&mut w, IncomparablePolicy::Error).unwrap_err();
+    assert_eq!(err.position(), v.iter().position(|x| x.is_nan()).unwrap());
+    assert!(w.iter().zip(&v).all(|(a, b)| a.to_bits() == b.to_bits()));
+
+    let mut w = expected.clone();
+    w.reverse();
+    assert_eq!(sort_partial(&mut w, IncomparablePolicy::Error), Ok(0));
+    assert_eq!(w, expected);
+}
+
+#[test]
+fn test_sort_partial_options() {
+    let mut v = vec![Some(3.0), None, Some(f64::NAN), Some(-1.0), None, Some(f64::NAN)];
+    assert_eq!(sort_partial(&mut v, IncomparablePolicy::Last), Ok(2));
+    assert_eq!(v[..4], [None, None, Some(-1.0), Some(3.0)]);
+    // `None` is comparable, but can be left out by the predicate instead.
+    let mut v = vec![Some(3.0), None, Some(-1.0)];
+    assert_eq!(sort_partial_by(&mut v, |x| x.is_none(), IncomparablePolicy::Last), Ok(1));
+    assert_eq!(v, [Some(-1.0), Some(3.0), None]);
+}
+
+// Closed intervals, where overlapping intervals are incomparable.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Interval(i32, i32);
+
+impl PartialOrd for Interval {
+    fn partial_cmp(&self, other: &Interval) -> Option<Ordering> {
+        if self == other {
+            Some(Ordering::Equal)
+        } else if self.1 < other.0 {
+            Some(Ordering::Less)
+        } else if other.1 < self.0 {
+            Some(Ordering::Greater)
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn test_sort_partial_intervals() {
+    let mut rng = weak_rng();
+    // Disjoint intervals sort into their order.
+    let mut v: Vec<Interval> = (0..200).map(|i| Interval(i * 10, i * 10 + 5)).collect();
+    let expected = v.clone();
+    rng.shuffle(&mut v[..]);
+    assert_eq!(sort_partial(&mut v, IncomparablePolicy::Error), Ok(0));
+    assert_eq!(v, expected);
+
+    // Overlapping intervals are still only reordered.
+    let mut v: Vec<Interval> = (0..1000).map(|_| {
+        let start = rng.gen_range(0, 1000);
+        Interval(start, start + rng.gen_range(0, 50))
+    }).collect();
+    let mut w = v.clone();
+    assert_eq!(sort_partial(&mut w, IncomparablePolicy::Last), Ok(0));
+    let key = |x: &Interval| (x.0, x.1);
+    v.sort_by_key(key);
+    w.sort_by_key(key);
+    assert_eq!(v, w);
+}
+
+#[test]
+#[cfg(not(feature="assert_working_compare"))]
+fn test_sort_partial_erratic() {
+    use std::cell::Cell;
+
+    // A value that compares however a random number generator says.
+    #[derive(Clone, Debug, PartialEq)]
+    struct Erratic(u32);
+
+    thread_local!(static STATE: Cell<u32> = const { Cell::new(1) });
+
+    impl PartialOrd for Erratic {
+        fn partial_cmp(&self, _: &Erratic) -> Option<Ordering> {
+            STATE.with(|s| {
+                let x = s.get().wrapping_mul(1_103_515_245).wrapping_add(12345);
+                s.set(x);
+                [Some(Ordering::Less), Some(Ordering::Greater), Some(Ordering::Equal), None][(x >> 16) as usize % 4]
+            })
+        }
+    }
+
+    for &len in &[5, 50, 5000] {
+        let mut v: Vec<Erratic> = (0..len).map(Erratic).collect();
+        let count = sort_partial(&mut v, IncomparablePolicy::First).unwrap();
+        assert!(count <= len as usize);
+        v.sort_by_key(|x| x.0);
+        assert!(v.iter().enumerate().all(|(i, x)| x.0 == i as u32));
+    }
+}
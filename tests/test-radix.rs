@@ -0,0 +1,100 @@
+#![cfg(feature = "alloc")]
+
+extern crate quickersort;
+extern crate rand;
+
+use quickersort::radix_sort;
+use rand::{Rand, Rng, weak_rng};
+use std::fmt::Debug;
+
+fn check<T: quickersort::RadixKey + Rand + Debug, F: Fn(T) -> T>(map: F) {
+    let mut rng = weak_rng();
+    for &len in &[0usize, 1, 10, 1000, 5000, 100_000] {
+        let v = rng.gen_iter::<T>().take(len).map(&map).collect::<Vec<_>>();
+        let mut sorted = v.clone();
+        sorted.sort();
+        let mut w = v.clone();
+        radix_sort(&mut w);
+        assert_eq!(w, sorted);
+        let mut reversed = sorted.clone();
+        reversed.reverse();
+        radix_sort(&mut reversed);
+        assert_eq!(reversed, sorted);
+    }
+}
+
+#[test]
+fn test_radix_sort_unsigned() {
+    check::<u8, _>(|x| x);
+    check::<u16, _>(|x| x);
+    check::<u32, _>(|x| x);
+    check::<u64, _>(|x| x);
+    check::<usize, _>(|x| x);
+    // Only the low bytes differ, so the high byte passes are skipped.
+    check::<u64, _>(|x| x % 1000);
+    check::<u32, _>(|x| x & 0xff00_00ff);
+}
+
+#[test]
+fn test_radix_sort_signed() {
+    check::<i8, _>(|x| x);
+    check::<i16, _>(|x| x);
+    check::<i32, _>(|x| x);
+    check::<i64, _>(|x| x);
+    check::<isize, _>(|x| x);
+    check::<i64, _>(|x| x % 1000);
+    check::<i32, _>(|x| if x % 2 == 0 { i32::MIN } else { i32::MAX });
+}
+
+#[test]
+fn test_radix_sort_128() {
+    let mut rng = weak_rng();
+    let v = rng.gen_iter::<(u64, i64)>().take(20_000)
+        .map(|(a, b)| ((a as i128) << 64 | b as u64 as i128, (b as u128) << 64 | a as u128))
+        .collect::<Vec<_>>();
+    let mut signed = v.iter().map(|x| x.0).collect::<Vec<_>>();
+    let mut unsigned = v.iter().map(|x| x.1).collect::<Vec<_>>();
+    let mut expected_signed = signed.clone();
+    expected_signed.sort();
+    let mut expected_unsigned = unsigned.clone();
+    expected_unsigned.sort();
+    radix_sort(&mut signed);
+    radix_sort(&mut unsigned);
+    assert_eq!(signed, expected_signed);
+    assert_eq!(unsigned, expected_unsigned);
+}
+
+#[test]
+fn test_radix_sort_other_keys() {
+    check::<bool, _>(|x| x);
+    check::<char, _>(|x| x);
+    check::<(u8, i16), _>(|x| x);
+    check::<(bool, char, u32), _>(|(a, b, c)| (a, b, c % 4));
+    check::<[i8; 3], _>(|x| x);
+    check::<([u16; 2], (i8,)), _>(|x| x);
+}
+
+#[test]
+fn test_radix_sort_by_key() {
+    let mut rng = weak_rng();
+    for &len in &[0usize, 1, 10, 1000, 100_000] {
+        for &modulus in &[2u64, 1000, u64::MAX] {
+            let v = rng.gen_iter::<(u64, u16)>().take(len).enumerate()
+                .map(|(i, (a, b))| (a % modulus, b, i.to_string()))
+                .collect::<Vec<_>>();
+            let mut expected = v.clone();
+            expected.sort_by_key(|x| x.0);
+            let mut w = v.clone();
+            quickersort::radix_sort_by_key(&mut w, |x| x.0);
+            assert_eq!(w, expected);
+
+            // Successive passes sort by several keys.
+            expected.sort_by_key(|x| (x.1, x.0));
+            w.sort_by_key(|x| x.2.len());
+            quickersort::radix_sort_by_key(&mut w, |x| x.2.parse::<u32>().unwrap());
+            quickersort::radix_sort_by_key(&mut w, |x| x.0);
+            quickersort::radix_sort_by_key(&mut w, |x| x.1);
+            assert_eq!(w, expected);
+        }
+    }
+}
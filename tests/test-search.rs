@@ -0,0 +1,127 @@
+extern crate quickersort;
+extern crate rand;
+
+use std::f64;
+use std::ops::{Bound, Range, RangeBounds};
+use quickersort::{float_lower_bound, float_upper_bound, float_equal_range, float_range};
+use quickersort::{sort_floats, SortedFloatsView, Total};
+use rand::{Rng, weak_rng};
+
+fn numbers() -> Vec<f64> {
+    vec![f64::NEG_INFINITY, -2.0, -1.0, -0.5, -0.0, 0.0, 0.5, 1.0, 2.0, f64::INFINITY, f64::NAN, -f64::NAN]
+}
+
+// Slices sorted by `sort_floats` with every mix of the numbers.
+fn slices() -> Vec<Vec<f64>> {
+    let mut rng = weak_rng();
+    let mut slices = vec![vec![]];
+    for &x in &numbers() {
+        slices.push(vec![x]);
+    }
+    for len in 2..60 {
+        for _ in 0..20 {
+            let mut v: Vec<f64> = (0..len).map(|_| *rng.choose(&numbers()).unwrap()).collect();
+            sort_floats(&mut v);
+            slices.push(v);
+        }
+    }
+    slices
+}
+
+// The positions of the elements in `range`, by a linear scan.
+fn expected_range<R: RangeBounds<f64>>(v: &[f64], range: R) -> Range<usize> {
+    let contains = |x: f64| {
+        (match range.start_bound() {
+            Bound::Included(&s) => Total(x) >= Total(s),
+            Bound::Excluded(&s) => Total(x) > Total(s),
+            Bound::Unbounded => true,
+        }) && (match range.end_bound() {
+            Bound::Included(&e) => Total(x) <= Total(e),
+            Bound::Excluded(&e) => Total(x) < Total(e),
+            Bound::Unbounded => true,
+        })
+    };
+    match v.iter().position(|&x| contains(x)) {
+        Some(start) => start..start + v[start..].iter().take_while(|&&x| contains(x)).count(),
+        None => {
+            // An empty range at the start bound.
+            let start = v.iter().take_while(|&&x| match range.start_bound() {
+                Bound::Included(&s) => Total(x) < Total(s),
+                Bound::Excluded(&s) => Total(x) <= Total(s),
+                Bound::Unbounded => false,
+            }).count();
+            start..start
+        }
+    }
+}
+
+#[test]
+fn test_float_bounds() {
+    for v in slices() {
+        let view = SortedFloatsView::new(&v);
+        assert_eq!(view.as_slice().len(), v.len());
+        for &x in &numbers() {
+            let lower = v.iter().take_while(|&&y| Total(y) < Total(x)).count();
+            let upper = v.iter().take_while(|&&y| Total(y) <= Total(x)).count();
+            assert_eq!(float_lower_bound(&v, x), lower);
+            assert_eq!(float_upper_bound(&v, x), upper);
+            assert_eq!(float_equal_range(&v, x), lower..upper);
+            assert_eq!(view.lower_bound(x), lower);
+            assert_eq!(view.upper_bound(x), upper);
+            assert_eq!(view.equal_range(x), lower..upper);
+        }
+    }
+}
+
+#[test]
+fn test_float_range() {
+    for v in slices() {
+        let view = SortedFloatsView::new(&v);
+        for &a in &numbers() {
+            for &b in &numbers() {
+                assert_eq!(float_range(&v, a..b), expected_range(&v, a..b));
+                assert_eq!(float_range(&v, a..=b), expected_range(&v, a..=b));
+                assert_eq!(view.range(a..b), expected_range(&v, a..b));
+                assert_eq!(view.range(a..=b), expected_range(&v, a..=b));
+                let excluded = (Bound::Excluded(a), Bound::Excluded(b));
+                assert_eq!(float_range(&v, excluded), expected_range(&v, excluded));
+            }
+            assert_eq!(float_range(&v, a..), expected_range(&v, a..));
+            assert_eq!(view.range(..=a), expected_range(&v, ..=a));
+        }
+        assert_eq!(float_range(&v, ..), 0..v.len());
+    }
+}
+
+#[test]
+fn test_sorted_floats_view_regions() {
+    for v in slices() {
+        let view = SortedFloatsView::new(&v);
+        let (neg_inf, pos_inf) = view.infinities();
+        let regions = [neg_inf.clone(), view.negatives(), view.zeros(), view.positives(), pos_inf.clone(),
+                       view.nans()];
+        let checks: [&dyn Fn(f64) -> bool; 6] = [
+            &|x| x == f64::NEG_INFINITY,
+            &|x| x < 0.0,
+            &|x| x == 0.0,
+            &|x| x > 0.0,
+            &|x| x == f64::INFINITY,
+            &|x| x.is_nan(),
+        ];
+        for (range, check) in regions.iter().zip(&checks) {
+            let expected = match v.iter().position(|&x| check(x)) {
+                Some(start) => start..start + v[start..].iter().take_while(|&&x| check(x)).count(),
+                None => range.start..range.start,
+            };
+            assert_eq!(*range, expected);
+        }
+        assert_eq!(view.negatives().start, neg_inf.start);
+        assert_eq!(view.positives().end, pos_inf.end);
+        assert_eq!(view.nans().end, v.len());
+    }
+
+    let v: Vec<f32> = vec![-1.0, 0.0, f32::INFINITY];
+    let view = SortedFloatsView::new(&v);
+    assert_eq!(view.infinities(), (0..0, 2..3));
+    assert_eq!(view.range(-0.0..=0.0), 1..2);
+}
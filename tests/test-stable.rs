@@ -0,0 +1,129 @@
+extern crate quickersort;
+extern crate rand;
+extern crate itertools;
+
+use quickersort::{stable_sort_in_place_by, stable_sort_by_with_buffer};
+#[cfg(feature = "alloc")]
+use quickersort::{stable_sort_by, stable_sort_by_key};
+use rand::{Rng, weak_rng};
+use itertools::Itertools;
+use std::panic::{self, AssertUnwindSafe};
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::mem::MaybeUninit;
+
+struct Counted<'a>(usize, &'a Cell<usize>);
+
+impl<'a> Drop for Counted<'a> {
+    fn drop(&mut self) {
+        self.1.set(self.1.get() + 1);
+    }
+}
+
+// Make the comparator panic at various points, and check that every element
+// is still dropped exactly once.
+macro_rules! do_test_panic_safety(
+    ($sortfun:ident) => ({
+        for &len in &[30usize, 100, 500] {
+            for panic_at in (0 .. 2000).step(97) {
+                let drops = Cell::new(0);
+                let mut v = weak_rng().gen_iter::<usize>().take(len)
+                                      .map(|x| Counted(x % 50, &drops)).collect::<Vec<_>>();
+                let calls = Cell::new(0);
+                let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+                    $sortfun(&mut v[..], &|a: &Counted, b: &Counted| {
+                        calls.set(calls.get() + 1);
+                        if calls.get() == panic_at {
+                            panic!("comparator panicked");
+                        }
+                        a.0.cmp(&b.0)
+                    });
+                }));
+                assert_eq!(drops.get(), 0);
+                drop(v);
+                assert_eq!(drops.get(), len);
+            }
+        }
+    })
+);
+
+fn patterns(len: usize) -> Vec<Vec<usize>> {
+    let mut rng = weak_rng();
+    let random = rng.gen_iter::<usize>().take(len).map(|x| x % 16).collect::<Vec<_>>();
+    let binary = rng.gen_iter::<usize>().take(len).map(|x| x % 2).collect::<Vec<_>>();
+    let mut sorted = random.clone();
+    sorted.sort();
+    let reversed = sorted.iter().cloned().rev().collect();
+    let sawtooth = (0 .. len).map(|i| i % 7).collect();
+    let mut runs = sorted.clone();
+    runs[.. len / 3].reverse();
+    vec![random, binary, sorted, reversed, sawtooth, runs]
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_stable_sort() {
+    for len in (0usize .. 600).step(13) {
+        for v in patterns(len) {
+            let mut w = v.into_iter().enumerate().map(|(i, x)| (x, i)).collect::<Vec<_>>();
+            let mut expected = w.clone();
+            expected.sort_by_key(|a| a.0);
+            stable_sort_by(&mut w[..], &|a, b| a.0.cmp(&b.0));
+            assert_eq!(w, expected);
+
+            expected.sort_by_key(|a| std::cmp::Reverse(a.0));
+            stable_sort_by_key(&mut w[..], |a| std::cmp::Reverse(a.0));
+            assert_eq!(w, expected);
+        }
+    }
+}
+
+#[test]
+fn test_stable_sort_in_place() {
+    for len in (0usize .. 600).step(13).chain(vec![1024, 5000]) {
+        for v in patterns(len) {
+            let mut w = v.into_iter().enumerate().map(|(i, x)| (x, i)).collect::<Vec<_>>();
+            let mut expected = w.clone();
+            expected.sort_by_key(|a| a.0);
+            stable_sort_in_place_by(&mut w[..], &|a, b| a.0.cmp(&b.0));
+            assert_eq!(w, expected);
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_stable_sort_panic_safety() {
+    do_test_panic_safety!(stable_sort_by);
+}
+
+#[test]
+fn test_stable_sort_in_place_panic_safety() {
+    do_test_panic_safety!(stable_sort_in_place_by);
+}
+
+fn sort_with_small_buffer<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C) {
+    let mut scratch = (0 .. v.len() / 8).map(|_| MaybeUninit::uninit()).collect::<Vec<_>>();
+    stable_sort_by_with_buffer(v, &mut scratch, compare);
+}
+
+#[test]
+fn test_stable_sort_with_buffer() {
+    for len in (0usize .. 600).step(13).chain(vec![1024, 5000]) {
+        for v in patterns(len) {
+            for &scratch_len in &[0, 1, len / 8, len / 2, len] {
+                let mut scratch = (0 .. scratch_len).map(|_| MaybeUninit::uninit()).collect::<Vec<_>>();
+                let mut w = v.iter().cloned().enumerate().map(|(i, x)| (x, i)).collect::<Vec<_>>();
+                let mut expected = w.clone();
+                expected.sort_by_key(|a| a.0);
+                stable_sort_by_with_buffer(&mut w[..], &mut scratch, &|a, b| a.0.cmp(&b.0));
+                assert_eq!(w, expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_stable_sort_with_buffer_panic_safety() {
+    do_test_panic_safety!(sort_with_small_buffer);
+}
@@ -0,0 +1,101 @@
+extern crate quickersort;
+extern crate rand;
+
+use quickersort::stats::{median_floats, quantile_floats, Interpolation, NanPolicy, StatsError};
+use rand::{Rng, weak_rng};
+
+const METHODS: [Interpolation; 5] = [Interpolation::Linear, Interpolation::Lower, Interpolation::Higher,
+                                     Interpolation::Nearest, Interpolation::Midpoint];
+
+// The quantile of sorted numbers, as NumPy computes it.
+fn expected_quantile(sorted: &[f64], q: f64, method: Interpolation) -> f64 {
+    let h = (sorted.len() - 1) as f64 * q;
+    let (lo, hi) = (h.floor() as usize, h.ceil() as usize);
+    let (a, b, t) = (sorted[lo], sorted[hi], h - h.floor());
+    match method {
+        Interpolation::Linear if a == b => a,
+        Interpolation::Linear => if t < 0.5 || b.is_infinite() { a + (b - a) * t } else { b - (b - a) * (1.0 - t) },
+        Interpolation::Lower => a,
+        Interpolation::Higher => b,
+        Interpolation::Nearest if t == 0.5 => if lo % 2 == 0 { a } else { b },
+        Interpolation::Nearest => sorted[h.round() as usize],
+        Interpolation::Midpoint => if lo == hi { a } else { b - (b - a) * 0.5 },
+    }
+}
+
+#[test]
+fn test_quantile_floats() {
+    let mut rng = weak_rng();
+    for &len in &[1usize, 2, 3, 10, 101, 1000] {
+        let v = rng.gen_iter::<f64>().map(|x| (1000.0 * (x - 0.5)).round()).take(len).collect::<Vec<_>>();
+        let mut sorted = v.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for &q in &[0.0, 0.1, 0.25, 0.5, 0.75, 0.9, 0.999, 1.0] {
+            for &method in &METHODS {
+                let mut w = v.clone();
+                assert_eq!(quantile_floats(&mut w[..], q, method, NanPolicy::Error),
+                           Ok(expected_quantile(&sorted, q, method)), "{} {} {:?}", len, q, method);
+                // The elements used are at their sorted positions.
+                let h = (len - 1) as f64 * q;
+                assert_eq!(w[h.floor() as usize], sorted[h.floor() as usize]);
+                assert_eq!(w[h.ceil() as usize], sorted[h.ceil() as usize]);
+            }
+        }
+        let mut w = v.clone();
+        assert_eq!(median_floats(&mut w[..], NanPolicy::Error), Ok(expected_quantile(&sorted, 0.5, METHODS[0])));
+    }
+
+    // Ties in `nearest` go to the even position.
+    let mut v = [1.0, 2.0, 3.0, 4.0];
+    assert_eq!(quantile_floats(&mut v, 0.5, Interpolation::Nearest, NanPolicy::Error), Ok(3.0));
+    assert_eq!(quantile_floats(&mut v, 1.0 / 6.0, Interpolation::Nearest, NanPolicy::Error), Ok(1.0));
+    let mut v = [f64::INFINITY, 1.0, f64::INFINITY];
+    assert_eq!(median_floats(&mut v, NanPolicy::Error), Ok(f64::INFINITY));
+    assert_eq!(quantile_floats(&mut v, 0.25, Interpolation::Linear, NanPolicy::Error), Ok(f64::INFINITY));
+}
+
+#[test]
+fn test_quantile_nan_policies() {
+    let mut v = [3.0, f64::NAN, 1.0, 2.0, f64::NAN];
+    assert!(median_floats(&mut v, NanPolicy::Propagate).unwrap().is_nan());
+    assert_eq!(median_floats(&mut v, NanPolicy::Error), Err(StatsError::Nan));
+    assert_eq!(median_floats(&mut v, NanPolicy::Ignore), Ok(2.0));
+    assert_eq!(quantile_floats(&mut v, 1.0, Interpolation::Linear, NanPolicy::Ignore), Ok(3.0));
+
+    let mut v = [f64::NAN, f64::NAN];
+    assert_eq!(median_floats(&mut v, NanPolicy::Ignore), Err(StatsError::Empty));
+    assert!(median_floats(&mut v, NanPolicy::Propagate).unwrap().is_nan());
+    let mut v: [f32; 0] = [];
+    assert_eq!(median_floats(&mut v, NanPolicy::Propagate), Err(StatsError::Empty));
+
+    let mut v = [quickersort::F16::from_f32(2.5), quickersort::F16::from_f32(-1.0)];
+    assert_eq!(median_floats(&mut v, NanPolicy::Error), Ok(0.75));
+}
+
+#[test]
+#[should_panic]
+fn test_quantile_out_of_range() {
+    let _ = quantile_floats(&mut [1.0, 2.0], 1.5, Interpolation::Linear, NanPolicy::Error);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_quantiles_floats() {
+    let mut rng = weak_rng();
+    let qs = [0.0, 0.999, 0.1, 0.5, 0.5, 0.25, 1.0, 0.75];
+    for &len in &[1usize, 2, 10, 1000] {
+        let mut v = rng.gen_iter::<f64>().map(|x| (1000.0 * (x - 0.5)).round()).take(len).collect::<Vec<_>>();
+        v.push(f64::NAN);
+        let mut sorted = v[..len].to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for &method in &METHODS {
+            let mut w = v.clone();
+            let expected = qs.iter().map(|&q| expected_quantile(&sorted, q, method)).collect::<Vec<_>>();
+            assert_eq!(quickersort::stats::quantiles_floats(&mut w[..], &qs, method, NanPolicy::Ignore),
+                       Ok(expected));
+        }
+        let mut w = v.clone();
+        let all_nan = quickersort::stats::quantiles_floats(&mut w[..], &qs, METHODS[0], NanPolicy::Propagate);
+        assert!(all_nan.unwrap().iter().all(|x| x.is_nan()));
+    }
+}
@@ -1,11 +1,18 @@
+#![allow(clippy::same_item_push)]
+
 extern crate quickersort;
 extern crate rand;
 extern crate itertools;
 
-use quickersort::{sort_by, insertion_sort, heapsort};
+use quickersort::{sort_by, insertion_sort, heapsort, select_nth_by, partial_sort_by, sort_range_by};
+use quickersort::{sort_by_mut, sort_by_key_mut, insertion_sort_mut, heapsort_mut};
+use quickersort::{try_sort_by, try_sort_by_key};
 use rand::{Rng, weak_rng};
 use itertools::Itertools;
-use std::cmp::Ordering::{Less, Greater};
+use std::cmp::Ordering;
+#[cfg(not(feature="assert_working_compare"))]
+use std::cmp::Ordering::{Less, Equal, Greater};
+use std::collections::HashMap;
 
 macro_rules! do_test_sort(
     ($sortfun:ident) => ({
@@ -52,6 +59,108 @@ fn test_insertion_sort() {
     do_test_sort!(insertion_sort);
 }
 
+// Run the `FnMut` sorts with a comparison function that counts its calls.
+macro_rules! counting(
+    ($name:ident, $sortfun:ident) => (
+        fn $name<C: Fn(&usize, &usize) -> Ordering>(v: &mut [usize], compare: &C) {
+            let mut count = 0;
+            $sortfun(v, &mut |a, b| { count += 1; compare(a, b) });
+            assert!(count + 1 >= v.len());
+        }
+    )
+);
+
+counting!(counting_sort_by_mut, sort_by_mut);
+counting!(counting_heapsort_mut, heapsort_mut);
+counting!(counting_insertion_sort_mut, insertion_sort_mut);
+
+#[test]
+fn test_sort_by_mut() {
+    do_test_sort!(counting_sort_by_mut);
+}
+
+#[test]
+fn test_heapsort_mut() {
+    do_test_sort!(counting_heapsort_mut);
+}
+
+#[test]
+fn test_insertion_sort_mut() {
+    do_test_sort!(counting_insertion_sort_mut);
+}
+
+#[test]
+fn test_sort_by_key_mut() {
+    // Memoize an expensive key without a `RefCell`.
+    let mut v: Vec<u32> = weak_rng().gen_iter::<u32>().map(|x| x % 100).take(1000).collect();
+    let mut cache = HashMap::new();
+    let mut computed = 0;
+    sort_by_key_mut(&mut v, |&x| cache.entry(x).or_insert_with(|| { computed += 1; x.to_string() }).clone());
+    assert!(computed <= 100);
+    assert!(v.windows(2).all(|w| w[0].to_string() <= w[1].to_string()));
+}
+
+#[test]
+fn test_try_sort_by() {
+    do_test_sort!(try_sort_by_ok);
+}
+
+fn try_sort_by_ok<C: Fn(&usize, &usize) -> Ordering>(v: &mut [usize], compare: &C) {
+    assert_eq!(try_sort_by(v, &mut |a, b| Ok::<_, ()>(compare(a, b))), Ok(()));
+}
+
+#[test]
+fn test_try_sort_by_error() {
+    let mut rng = weak_rng();
+    for &len in &[2, 10, 100, 1000, 10000] {
+        for &fail_at in &[0, 1, len / 2, len * 5] {
+            let mut v: Vec<usize> = rng.gen_iter::<usize>().map(|x| x % len).take(len).collect();
+            let mut expected = v.clone();
+            let mut calls = 0;
+            let result = try_sort_by(&mut v, &mut |a, b| {
+                calls += 1;
+                if calls > fail_at { Err(calls) } else { Ok(a.cmp(b)) }
+            });
+            // No more calls after the first error.
+            if let Err(at) = result {
+                assert_eq!(at, fail_at + 1);
+                assert_eq!(calls, at);
+            } else {
+                assert!(calls <= fail_at);
+            }
+            v.sort();
+            expected.sort();
+            assert_eq!(v, expected);
+        }
+    }
+}
+
+#[test]
+fn test_try_sort_by_key() {
+    let mut v = vec!["4", "20", "3", "100"];
+    assert_eq!(try_sort_by_key(&mut v, |s| s.parse::<u32>()), Ok(()));
+    assert_eq!(v, ["3", "4", "20", "100"]);
+    v.push("many");
+    assert!(try_sort_by_key(&mut v, |s| s.parse::<u32>()).is_err());
+    v.sort();
+    assert_eq!(v, ["100", "20", "3", "4", "many"]);
+}
+
+#[test]
+#[cfg(not(feature="assert_working_compare"))]
+fn test_sort_by_mut_erratic() {
+    // Answers taken from a sequence, as the fuzzing harness does.
+    let mut rng = weak_rng();
+    for &len in &[10, 100, 1000] {
+        let answers: Vec<Ordering> = (0..len * 20).map(|_| *rng.choose(&[Less, Equal, Greater]).unwrap()).collect();
+        let mut v: Vec<usize> = (0..len).collect();
+        let mut pos = 0;
+        sort_by_mut(&mut v, &mut |_, _| { pos += 1; answers.get(pos).cloned().unwrap_or(Equal) });
+        v.sort();
+        assert!(v.iter().enumerate().all(|(i, &x)| i == x));
+    }
+}
+
 #[test]
 #[cfg(not(feature="assert_working_compare"))]
 fn test_always_less() {
@@ -72,3 +181,90 @@ fn test_always_greater() {
     sort_by(&mut v, &|_, _| Greater);
 }
 
+#[test]
+fn test_select_nth() {
+    let cmp = |a: &usize, b: &usize| a.cmp(b);
+    for len in (1usize .. 250).step(7) {
+        for modulus in &[2usize, 10, 1000] {
+            let v = weak_rng().gen_iter::<usize>().take(len).map(|x| x % modulus)
+                              .collect::<Vec<usize>>();
+            let mut sorted = v.clone();
+            sorted.sort();
+            let mut patterns = vec![v.clone(), sorted.clone()];
+            patterns.push(sorted.iter().cloned().rev().collect());
+            for pattern in patterns {
+                for k in (0 .. len).step(3) {
+                    let mut w = pattern.clone();
+                    {
+                        let (left, nth, right) = select_nth_by(&mut w[..], k, &cmp);
+                        assert_eq!(*nth, sorted[k]);
+                        assert!(left.iter().all(|x| *x <= sorted[k]));
+                        assert!(right.iter().all(|x| *x >= sorted[k]));
+                    }
+                    w.sort();
+                    assert_eq!(w, sorted);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+#[cfg(not(feature="assert_working_compare"))]
+fn test_select_nth_broken_compare() {
+    let mut v = vec![0u8; 256];
+    select_nth_by(&mut v, 17, &|_, _| Less);
+    select_nth_by(&mut v, 200, &|_, _| Greater);
+}
+
+#[test]
+fn test_partial_sort() {
+    let cmp = |a: &usize, b: &usize| a.cmp(b);
+    for len in (0usize .. 250).step(7) {
+        for _ in 0 .. 10 {
+            let v = weak_rng().gen_iter::<usize>().take(len).map(|x| x % 100)
+                              .collect::<Vec<usize>>();
+            let mut sorted = v.clone();
+            sorted.sort();
+            for k in (0 .. len + 2).step(5) {
+                let mut w = v.clone();
+                partial_sort_by(&mut w[..], k, &cmp);
+                let k = std::cmp::min(k, len);
+                assert_eq!(w[..k], sorted[..k]);
+                w.sort();
+                assert_eq!(w, sorted);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_sort_range() {
+    let cmp = |a: &usize, b: &usize| a.cmp(b);
+    for len in (0usize .. 250).step(7) {
+        for _ in 0 .. 10 {
+            let v = weak_rng().gen_iter::<usize>().take(len).map(|x| x % 100)
+                              .collect::<Vec<usize>>();
+            let mut sorted = v.clone();
+            sorted.sort();
+            for a in (0 .. len + 1).step(11) {
+                for b in (a .. len + 1).step(6) {
+                    let mut w = v.clone();
+                    sort_range_by(&mut w[..], a..b, &cmp);
+                    assert_eq!(w[a..b], sorted[a..b]);
+                    if a < len {
+                        assert!(w[..a].iter().all(|x| *x <= sorted[a]));
+                    }
+                    if b > 0 {
+                        assert!(w[b..].iter().all(|x| *x >= sorted[b - 1]));
+                    }
+                    if a == b && a < len {
+                        assert_eq!(w[a], sorted[a]);
+                    }
+                    w.sort();
+                    assert_eq!(w, sorted);
+                }
+            }
+        }
+    }
+}